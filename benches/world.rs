@@ -0,0 +1,206 @@
+// These lines disable certain warnings from Clippy, a Rust linter.
+// Useful for focusing on core logic, but good to address these in larger projects.
+#![allow(clippy::pedantic, clippy::nursery, clippy::manual_range_contains)]
+//! Measures how long a frame's collision checks and culling take as levels grow,
+//! with the spatial grid and with the old approach of looking at every entity.
+//! Run it with `cargo bench --bench world`. It needs no window, so it runs anywhere.
+//!
+//! The per-entity systems (moving platforms, chicken behaviours) still visit every entity,
+//! so their cost is shown too: it grows with the level, which is why endless mode
+//! throws away everything the players have left behind.
+
+use easter_egg::constants::*;
+use easter_egg::entities::chicken::chicken_system;
+use easter_egg::entities::platform::{platform_system, platforms, platforms_in};
+use easter_egg::entities::player::{Controls, Player};
+use easter_egg::level::Level;
+use easter_egg::options::DifficultySettings;
+use easter_egg::world::components::Hazard;
+use easter_egg::world::systems::{hazard_system, movement_system};
+use easter_egg::world::{Entity, World};
+use macroquad::prelude::*;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// How many frames are timed for each level size.
+const FRAMES: u32 = 300;
+/// One frame at 60 frames per second.
+const DELTA_TIME: f32 = 1.0 / 60.0;
+/// How many places along each level the grid's answers are checked against a scan.
+const CHECKED_VIEWS: u32 = 50;
+/// The area of the world on screen, about as big as a typical window.
+const VIEW_SIZE: Vec2 = Vec2::new(1280.0, 768.0);
+
+/// The strip just under the player's feet where the ground check looks for platforms.
+fn feet(rect: Rect) -> Rect {
+    Rect::new(
+        rect.x,
+        rect.bottom() - GROUND_DETECTION_BUFFER,
+        rect.w,
+        GROUND_DETECTION_BUFFER * 2.0,
+    )
+}
+
+/// The collision checks and culling one frame does, using the grid.
+/// Returns how many entities would be drawn, so the work can't be optimised away.
+fn grid_frame(player: &Player, level: &Level, view: Rect) -> usize {
+    let world = &level.world;
+    let rect = player.body.entity.rect;
+    let bounds = player.body.entity.get_collision_bounds();
+    let feet = feet(rect);
+    let ground = platforms_in(world, feet)
+        .find(|(_, platform_rect, platform)| platform.is_solid() && feet.overlaps(platform_rect));
+    black_box(ground.map(|(entity, _, _)| entity));
+    black_box(player.touches_spike(level));
+    let touching = world
+        .query(bounds)
+        .into_iter()
+        .filter_map(|entity| world.collision_bounds(entity))
+        .filter(|other| bounds.overlaps(other))
+        .count();
+    black_box(touching);
+    world
+        .query(view)
+        .into_iter()
+        .filter(|entity| world.sprites.get(*entity).is_some())
+        .count()
+}
+
+/// The same work as `grid_frame`, looking at every entity instead of asking the grid.
+fn scan_frame(player: &Player, world: &World, view: Rect) -> usize {
+    let rect = player.body.entity.rect;
+    let bounds = player.body.entity.get_collision_bounds();
+    let feet = feet(rect);
+    let ground = platforms(world)
+        .find(|(_, platform_rect, platform)| platform.is_solid() && feet.overlaps(platform_rect));
+    black_box(ground.map(|(entity, _, _)| entity));
+    let spike = world
+        .hazards
+        .iter()
+        .filter(|(_, hazard)| matches!(hazard, Hazard::Sharp))
+        .filter_map(|(entity, _)| world.collision_bounds(entity))
+        .any(|spike| bounds.overlaps(&spike));
+    black_box(spike);
+    let touching = world
+        .colliders
+        .iter()
+        .filter_map(|(entity, _)| world.collision_bounds(entity))
+        .filter(|other| bounds.overlaps(other))
+        .count();
+    black_box(touching);
+    world
+        .sprites
+        .iter()
+        .filter_map(|(entity, _)| world.transforms.get(entity))
+        .filter(|transform| transform.rect.overlaps(&view))
+        .count()
+}
+
+/// Checks that the grid finds exactly the entities that looking at every entity finds,
+/// both touching the player's `bounds` and in `view`, so the timings compare two ways of
+/// doing the same work. A grid that missed entities would be fast, but it would lose collisions.
+fn check_grid_matches_scan(world: &World, bounds: Rect, view: Rect) {
+    let touching = |entity: &Entity| {
+        world
+            .collision_bounds(*entity)
+            .is_some_and(|other| bounds.overlaps(&other))
+    };
+    let visible = |entity: &Entity| {
+        world.sprites.get(*entity).is_some()
+            && world
+                .transforms
+                .get(*entity)
+                .is_some_and(|transform| transform.rect.overlaps(&view))
+    };
+    let grid_touching: Vec<Entity> = world.query(bounds).into_iter().filter(touching).collect();
+    let scan_touching: Vec<Entity> = world
+        .colliders
+        .iter()
+        .map(|(entity, _)| entity)
+        .filter(touching)
+        .collect();
+    assert_eq!(grid_touching, scan_touching, "touching {bounds:?}");
+    let grid_visible: Vec<Entity> = world.query(view).into_iter().filter(visible).collect();
+    let scan_visible: Vec<Entity> = world
+        .sprites
+        .iter()
+        .map(|(entity, _)| entity)
+        .filter(visible)
+        .collect();
+    assert_eq!(grid_visible, scan_visible, "visible in {view:?}");
+}
+
+/// Runs `frame` `FRAMES` times and returns the average time per frame.
+fn time_frames(mut frame: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        frame();
+    }
+    start.elapsed() / FRAMES
+}
+
+fn main() {
+    println!(
+        "{:>9} {:>9} {:>14} {:>14} {:>14}",
+        "chunks", "entities", "grid (us)", "scan (us)", "systems (us)"
+    );
+    for chunks in [4, 40, 400, 1000] {
+        // Build an endless level `chunks` chunks long, at the chosen difficulty throughout.
+        let mut level = Level::endless(1, RACE_LEVEL_BOTTOM);
+        for _ in 0..chunks {
+            level.extend_endless(&DifficultySettings::NORMAL, 1.0);
+        }
+
+        // Put the player halfway along the level, with the view around them.
+        let mut player = Player::new(Controls::ARROWS, WHITE, 1, 0.0);
+        let middle = Vec2::new(level.generated_to / 2.0, PLAYER_START_POS.y);
+        player.respawn(middle);
+        // Check for spikes every frame, like a player who isn't protected after losing a life.
+        player.invulnerable_time = 0.0;
+        // Let the player land once, so both ways are timed against the same standing player.
+        player.step(&level, DELTA_TIME);
+        let view = Rect::new(
+            middle.x - VIEW_SIZE.x / 2.0,
+            RACE_LEVEL_BOTTOM - VIEW_SIZE.y,
+            VIEW_SIZE.x,
+            VIEW_SIZE.y,
+        );
+
+        // Before timing anything, make sure both ways give the same answers all along the level.
+        for step in 0..=CHECKED_VIEWS {
+            let x = level.generated_to * step as f32 / CHECKED_VIEWS as f32;
+            let check_view = Rect { x, ..view };
+            let bounds = Rect::new(x, PLAYER_START_POS.y, PLAYER_SIZE.x, PLAYER_SIZE.y);
+            check_grid_matches_scan(&level.world, bounds, check_view);
+        }
+        check_grid_matches_scan(
+            &level.world,
+            player.body.entity.get_collision_bounds(),
+            view,
+        );
+
+        let grid = time_frames(|| {
+            black_box(grid_frame(&player, &level, view));
+        });
+        let scan = time_frames(|| {
+            black_box(scan_frame(&player, &level.world, view));
+        });
+        let player_center = player.body.entity.rect.center();
+        let systems = time_frames(|| {
+            let world = &mut level.world;
+            platform_system(world, &[], DELTA_TIME);
+            movement_system(world, DELTA_TIME);
+            hazard_system(world, DELTA_TIME);
+            chicken_system(world, &[player_center], DELTA_TIME);
+        });
+
+        println!(
+            "{:>9} {:>9} {:>14.1} {:>14.1} {:>14.1}",
+            chunks,
+            level.world.entity_count(),
+            grid.as_secs_f64() * 1e6,
+            scan.as_secs_f64() * 1e6,
+            systems.as_secs_f64() * 1e6,
+        );
+    }
+}
@@ -0,0 +1,778 @@
+//! Loading the game's images, sounds and font.
+//!
+//! Every asset has a name (like "player_right", "chicken" or "background_music") and a default
+//! file embedded in the game. Asset packs can replace any of them without recompiling: a pack
+//! is a directory with a `manifest.txt` that maps asset names to files in the directory.
+//! For example, a Halloween pack could have this manifest:
+//!
+//! ```text
+//! # Bats instead of chickens, and spookier music.
+//! chicken = bat.png
+//! background = night_sky.png
+//! background_music = sounds/spooky.ogg
+//! ```
+//!
+//! Packs are picked with `--pack directory` (more than one can be given; later packs win).
+//! Anything a pack doesn't mention keeps its default.
+//!
+//! The full-screen pictures with words in them ("game_start", "game_over" and "win") can also
+//! be translated: a pack line like `game_start.pt = inicio.png` gives the picture shown when the
+//! game is in Portuguese (`pt` being the language's code). Languages without a translated
+//! picture show the default one.
+
+use crate::atlas::{pack_atlases, TextureRegion};
+use crate::constants::*;
+use crate::display::Display;
+use crate::locale::Language;
+use macroquad::audio::{load_sound_from_bytes, Sound};
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Holds all the textures (images) and sounds used in the game.
+/// Loading these upfront helps prevent lag during gameplay.
+/// The smaller images share texture atlases, so each image is a region of a texture.
+/// Sounds that failed to load are `None`, and play as silence; a font that failed to load is
+/// `None`, and text is drawn in Macroquad's built-in font.
+pub struct Assets {
+    // Player textures
+    pub player_right: TextureRegion,
+    pub player_left: TextureRegion,
+    // Object textures
+    pub platform: TextureRegion,
+    pub choco_bar: TextureRegion,     // Moving platforms
+    pub platform_tile: TextureRegion, // Crumbling platforms
+    pub cookie_tile: TextureRegion,   // Bouncy platforms
+    pub choco_tile: TextureRegion,    // Timed platforms
+    pub chicken: TextureRegion,
+    pub spike: TextureRegion,
+    pub egg: TextureRegion,
+    // UI / Screen textures
+    pub game_over: TextureRegion,
+    pub win: TextureRegion,
+    pub game_start: TextureRegion,
+    pub score_panel: TextureRegion,
+    // Environment textures
+    pub cloud: TextureRegion,
+    pub house: TextureRegion, // The end goal structure
+    pub background: TextureRegion,
+    // Fun extras
+    pub meme_textures: [TextureRegion; 8], // An array to hold multiple meme images
+    // Sound effects
+    pub jump: Option<Sound>,
+    pub egg_collect: Option<Sound>,
+    pub chicken_hit: Option<Sound>,
+    pub spike_hit: Option<Sound>,
+    pub magic: Option<Sound>, // Sound for reaching the house without enough eggs
+    // Music
+    pub background_music: Option<Sound>,
+    pub game_over_sound: Option<Sound>,
+    pub win_sound: Option<Sound>,
+    // Text
+    pub font: Option<Font>, // `None` uses Macroquad's built-in font
+    // Translated pictures, as (picture name, language, picture), from asset packs
+    pub translations: Vec<(&'static str, Language, TextureRegion)>,
+}
+
+impl Assets {
+    /// The image for the asset called `name`, to replace it, or `None` if there is no such image.
+    pub fn texture_mut(&mut self, name: &str) -> Option<&mut TextureRegion> {
+        let texture = match name {
+            "player_right" => &mut self.player_right,
+            "player_left" => &mut self.player_left,
+            "platform" => &mut self.platform,
+            "choco_bar" => &mut self.choco_bar,
+            "platform_tile" => &mut self.platform_tile,
+            "cookie_tile" => &mut self.cookie_tile,
+            "choco_tile" => &mut self.choco_tile,
+            "chicken" => &mut self.chicken,
+            "spike" => &mut self.spike,
+            "egg" => &mut self.egg,
+            "game_over" => &mut self.game_over,
+            "win" => &mut self.win,
+            "game_start" => &mut self.game_start,
+            "score_panel" => &mut self.score_panel,
+            "cloud" => &mut self.cloud,
+            "house" => &mut self.house,
+            "background" => &mut self.background,
+            // "meme1" to "meme8" are the meme textures, in order.
+            _ => {
+                let number: usize = name.strip_prefix("meme")?.parse().ok()?;
+                self.meme_textures.get_mut(number.checked_sub(1)?)?
+            }
+        };
+        Some(texture)
+    }
+
+    /// The picture called `name` (e.g. "game_over") translated into `language`, if an asset pack
+    /// has a translation. Otherwise the default picture should be shown.
+    pub fn translation(&self, name: &str, language: Language) -> Option<&TextureRegion> {
+        self.translations
+            .iter()
+            .find(|(picture, picture_language, _)| {
+                *picture == name && *picture_language == language
+            })
+            .map(|(_, _, texture)| texture)
+    }
+
+    /// The sound for the asset called `name`, to replace it, or `None` if there is no such sound.
+    pub fn sound_mut(&mut self, name: &str) -> Option<&mut Option<Sound>> {
+        match name {
+            "jump" => Some(&mut self.jump),
+            "egg_collect" => Some(&mut self.egg_collect),
+            "chicken_hit" => Some(&mut self.chicken_hit),
+            "spike_hit" => Some(&mut self.spike_hit),
+            "magic" => Some(&mut self.magic),
+            "background_music" => Some(&mut self.background_music),
+            "game_over_sound" => Some(&mut self.game_over_sound),
+            "win_sound" => Some(&mut self.win_sound),
+            _ => None,
+        }
+    }
+}
+
+/// Why an asset (or asset pack) couldn't be loaded.
+/// The game still starts, using the default asset or a placeholder instead.
+#[derive(Debug)]
+pub enum AssetError {
+    /// An asset pack whose manifest couldn't be read. The whole pack is skipped.
+    BadPack {
+        /// The pack's directory.
+        directory: PathBuf,
+        /// Why the manifest couldn't be read.
+        reason: String,
+    },
+    /// A manifest line naming an asset the game doesn't have. The line is ignored.
+    UnknownAsset {
+        /// The pack's directory.
+        directory: PathBuf,
+        /// The name in the manifest.
+        name: String,
+    },
+    /// A file listed in a pack's manifest that couldn't be read. The default is used instead.
+    Missing {
+        /// The asset's name, e.g. "chicken".
+        name: &'static str,
+        /// The file the pack said to use.
+        path: PathBuf,
+        /// Why the file couldn't be read.
+        reason: String,
+    },
+    /// An image that couldn't be decoded. Replaced by the default,
+    /// or by a checkerboard if it was the default that failed.
+    BadTexture {
+        /// The asset's name, e.g. "player_right".
+        name: &'static str,
+        /// What the image decoder said was wrong.
+        reason: String,
+    },
+    /// A font that couldn't be read. Replaced by the default,
+    /// or by Macroquad's built-in font if it was the default that failed.
+    BadFont {
+        /// The asset's name, e.g. "font".
+        name: &'static str,
+        /// What the font reader said was wrong.
+        reason: String,
+    },
+    /// A sound that couldn't be decoded. Replaced by the default,
+    /// or by silence if it was the default that failed.
+    BadSound {
+        /// The asset's name, e.g. "jump".
+        name: &'static str,
+        /// What the audio decoder said was wrong.
+        reason: String,
+    },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::BadPack { directory, reason } => {
+                write!(
+                    f,
+                    "could not load the asset pack {}: {reason}",
+                    directory.display()
+                )
+            }
+            AssetError::UnknownAsset { directory, name } => {
+                write!(
+                    f,
+                    "the asset pack {} replaces \"{name}\", which the game doesn't have",
+                    directory.display()
+                )
+            }
+            AssetError::Missing { name, path, reason } => {
+                write!(
+                    f,
+                    "could not read {} for \"{name}\": {reason}",
+                    path.display()
+                )
+            }
+            AssetError::BadTexture { name, reason } => {
+                write!(f, "could not load the image \"{name}\": {reason}")
+            }
+            AssetError::BadFont { name, reason } => {
+                write!(f, "could not load the font \"{name}\": {reason}")
+            }
+            AssetError::BadSound { name, reason } => {
+                write!(f, "could not load the sound \"{name}\": {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
+/// An asset's name, and its default file (relative to the `assets` directory) embedded in the game.
+pub struct EmbeddedAsset {
+    /// The name asset packs use for it, e.g. "player_right".
+    pub name: &'static str,
+    /// The default file, e.g. "character/c_right.png".
+    pub path: &'static str,
+    /// The default file's contents.
+    pub bytes: &'static [u8],
+}
+
+/// Builds an `EmbeddedAsset`, embedding the file with `include_bytes!`,
+/// which puts the file content directly into the compiled program.
+macro_rules! embedded {
+    ($name:literal, $path:literal) => {
+        EmbeddedAsset {
+            name: $name,
+            path: $path,
+            bytes: include_bytes!(concat!("../assets/", $path)),
+        }
+    };
+}
+
+/// Every image in the game, with its default file.
+pub const TEXTURES: [EmbeddedAsset; 25] = [
+    embedded!("player_right", "character/c_right.png"),
+    embedded!("player_left", "character/c_left.png"),
+    embedded!("platform", "platforms/platform.png"),
+    embedded!("choco_bar", "platforms/choco_bar.png"),
+    embedded!("platform_tile", "platforms/platform_bar_tile.png"),
+    embedded!("cookie_tile", "tiles/cookie_tile.png"),
+    embedded!("choco_tile", "tiles/choco_tile.png"),
+    embedded!("chicken", "chickens/chicken_fly_1.png"),
+    embedded!("spike", "spikes/spike_1.png"),
+    embedded!("egg", "eggs/easter_egg_1.png"),
+    // Game state screens
+    embedded!("game_over", "gui/game_over_cesta.png"),
+    embedded!("win", "gui/end.png"),
+    embedded!("game_start", "gui/game_start.png"),
+    embedded!("score_panel", "gui/bar_panel.png"),
+    // Environment
+    embedded!("cloud", "clouds/clouds.png"),
+    embedded!("house", "house/houseplat.png"),
+    embedded!("background", "background/chocobackground.png"),
+    // Memes for the game over screen
+    embedded!("meme1", "gui/meme1.png"),
+    embedded!("meme2", "gui/meme2.png"),
+    embedded!("meme3", "gui/meme3.png"),
+    embedded!("meme4", "gui/meme4.png"),
+    embedded!("meme5", "gui/meme5.png"),
+    embedded!("meme6", "gui/meme6.png"),
+    embedded!("meme7", "gui/meme7.png"),
+    embedded!("meme8", "gui/meme8.png"),
+];
+
+/// Every sound in the game, with its default file.
+pub const SOUNDS: [EmbeddedAsset; 8] = [
+    embedded!("jump", "sounds/ogg/jump.ogg"),
+    embedded!("egg_collect", "sounds/ogg/check.ogg"),
+    embedded!("chicken_hit", "sounds/ogg/monster_scream.ogg"),
+    embedded!("spike_hit", "sounds/ogg/bump.ogg"),
+    embedded!("magic", "sounds/ogg/magic.ogg"),
+    embedded!("background_music", "sounds/ogg/music_theme.ogg"),
+    embedded!("game_over_sound", "sounds/ogg/water_splash.ogg"),
+    embedded!("win_sound", "sounds/ogg/success.ogg"),
+];
+
+/// Every font in the game, with its default file.
+pub const FONTS: [EmbeddedAsset; 1] = [embedded!("font", "fonts/DejaVuSans-Bold.ttf")];
+
+/// A translation of one of the pictures with words in them. The game has none built in,
+/// so they only come from asset packs.
+pub struct TranslatedAsset {
+    /// The name asset packs use for it: the picture's name and the language's code,
+    /// e.g. "game_over.pt".
+    pub name: &'static str,
+    /// The picture it translates, e.g. "game_over".
+    pub picture: &'static str,
+    /// The language of the words in it.
+    pub language: Language,
+}
+
+/// Builds a `TranslatedAsset`, naming it after the picture and the language's code.
+macro_rules! translated {
+    ($picture:literal, $language:ident, $code:literal) => {
+        TranslatedAsset {
+            name: concat!($picture, ".", $code),
+            picture: $picture,
+            language: Language::$language,
+        }
+    };
+}
+
+/// Every translation an asset pack can give.
+pub const TRANSLATED_TEXTURES: [TranslatedAsset; 6] = [
+    translated!("game_start", English, "en"),
+    translated!("game_start", Portuguese, "pt"),
+    translated!("game_over", English, "en"),
+    translated!("game_over", Portuguese, "pt"),
+    translated!("win", English, "en"),
+    translated!("win", Portuguese, "pt"),
+];
+
+/// Decodes a PNG image from byte data.
+/// `bytes`: A slice of bytes representing the PNG file data.
+pub fn load_png_image_from_bytes(bytes: &[u8]) -> Result<Image, String> {
+    // `None` means Macroquad tries to auto-detect the format.
+    Image::from_file_with_format(bytes, None).map_err(|error| error.to_string())
+}
+
+/// Turns an image into a Macroquad texture of its own.
+fn texture_from_image(image: &Image) -> Texture2D {
+    let texture = Texture2D::from_image(image);
+    // Set the texture filtering mode to Nearest. This prevents blurring in pixel art.
+    texture.set_filter(FilterMode::Nearest);
+    texture // Return the loaded texture
+}
+
+/// Loads a PNG image from byte data into a Macroquad texture of its own.
+/// `bytes`: A slice of bytes representing the PNG file data.
+pub fn load_png_texture_from_bytes(bytes: &[u8]) -> Result<Texture2D, String> {
+    Ok(texture_from_image(&load_png_image_from_bytes(bytes)?))
+}
+
+/// A magenta and black checkerboard, drawn in place of images that failed to load
+/// so they are easy to spot.
+fn placeholder_image() -> Image {
+    let mut image = Image::gen_image_color(8, 8, PLACEHOLDER_COLORS[0]);
+    for y in 0..8 {
+        for x in 0..8 {
+            if (x / 4 + y / 4) % 2 == 1 {
+                image.set_pixel(x, y, PLACEHOLDER_COLORS[1]);
+            }
+        }
+    }
+    image
+}
+
+/// Wraps a future, turning a panic while it runs into an `Err`.
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = std::thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+        let inner = self.0.as_mut();
+        match std::panic::catch_unwind(AssertUnwindSafe(|| inner.poll(context))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
+
+/// Loads a sound (OGG, WAV or FLAC) from byte data.
+/// `bytes`: A slice of bytes representing the sound file data.
+pub async fn load_sound(bytes: &[u8]) -> Result<Sound, String> {
+    // Macroquad's audio decoder panics on data it can't read instead of returning an error,
+    // so catch the panic, with the usual panic message silenced while we do.
+    let panic_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = CatchUnwind(Box::pin(load_sound_from_bytes(bytes))).await;
+    std::panic::set_hook(panic_hook);
+    match result {
+        Ok(Ok(sound)) => Ok(sound),
+        Ok(Err(error)) => Err(error.to_string()),
+        Err(_) => Err("not a sound file the game can play".to_owned()),
+    }
+}
+
+/// Draws the loading screen: a title, a progress bar and the name of what is loading.
+/// `done`: How many assets have been loaded so far.
+/// `total`: How many assets there are to load.
+/// `name`: The name of the asset being loaded now.
+/// The game's font may not be loaded yet, so this screen uses Macroquad's built-in one.
+fn draw_loading_screen(display: &Display, done: usize, total: usize, name: &str) {
+    display.begin();
+    clear_background(BACKGROUND_COLOR);
+    let width = VIRTUAL_WIDTH * 0.6;
+    let height = VIRTUAL_HEIGHT * 0.04;
+    let x = (VIRTUAL_WIDTH - width) / 2.0;
+    let y = VIRTUAL_HEIGHT * 0.55;
+    draw_text(
+        "Loading...",
+        x,
+        y - height,
+        0.06 * VIRTUAL_HEIGHT,
+        LOADING_TEXT_COLOR,
+    );
+    // The bar fills up as assets are loaded.
+    draw_rectangle_lines(x, y, width, height, 2.0, LOADING_TEXT_COLOR);
+    let progress = done as f32 / total.max(1) as f32;
+    draw_rectangle(x, y, width * progress, height, LOADING_BAR_COLOR);
+    draw_text(
+        name,
+        x,
+        y + height * 2.5,
+        0.03 * VIRTUAL_HEIGHT,
+        LOADING_TEXT_COLOR,
+    );
+    display.present();
+}
+
+/// A directory of files replacing some of the game's assets.
+pub struct AssetPack {
+    /// The directory the pack was loaded from.
+    pub directory: PathBuf,
+    /// The replacement file for each asset the pack changes, as (asset name, file path) pairs,
+    /// in the order the manifest lists them.
+    pub files: Vec<(String, PathBuf)>,
+}
+
+impl AssetPack {
+    /// Reads the manifest of the asset pack in `directory`.
+    /// Each manifest line looks like "chicken = bat.png", with the file relative to the directory.
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn load(directory: &Path) -> Result<AssetPack, AssetError> {
+        let manifest =
+            std::fs::read_to_string(directory.join(ASSET_MANIFEST_FILE)).map_err(|error| {
+                AssetError::BadPack {
+                    directory: directory.to_owned(),
+                    reason: error.to_string(),
+                }
+            })?;
+        let mut files = Vec::new();
+        for (number, line) in manifest.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, file)) = line.split_once('=') else {
+                return Err(AssetError::BadPack {
+                    directory: directory.to_owned(),
+                    reason: format!("line {} should look like \"name = file\"", number + 1),
+                });
+            };
+            files.push((name.trim().to_owned(), directory.join(file.trim())));
+        }
+        Ok(AssetPack {
+            directory: directory.to_owned(),
+            files,
+        })
+    }
+
+    /// The file this pack replaces the asset called `name` with, if it replaces it.
+    pub fn file_for(&self, name: &str) -> Option<&Path> {
+        self.files
+            .iter()
+            .rev()
+            .find(|(asset, _)| asset == name)
+            .map(|(_, path)| path.as_path())
+    }
+}
+
+/// Loads the assets one at a time, showing the loading screen between them,
+/// and remembers which ones failed.
+struct Loader {
+    /// The packs in use, in the order they were given. Later packs win.
+    packs: Vec<AssetPack>,
+    /// The decoded images, until they are packed into atlases.
+    images: HashMap<&'static str, Image>,
+    /// Where each image ended up once packed.
+    textures: HashMap<&'static str, TextureRegion>,
+    sounds: HashMap<&'static str, Sound>,
+    fonts: HashMap<&'static str, Font>,
+    translations: Vec<(&'static str, Language, TextureRegion)>,
+    errors: Vec<AssetError>,
+}
+
+impl Loader {
+    /// The contents of the file the packs replace the asset called `name` with, if any do.
+    /// Files that can't be read are reported, and the next pack down (or the default) is used.
+    fn replacement(&mut self, name: &'static str) -> Option<(PathBuf, Vec<u8>)> {
+        for pack in self.packs.iter().rev() {
+            let Some(path) = pack.file_for(name) else {
+                continue;
+            };
+            match std::fs::read(path) {
+                Ok(bytes) => return Some((path.to_owned(), bytes)),
+                Err(error) => self.errors.push(AssetError::Missing {
+                    name,
+                    path: path.to_owned(),
+                    reason: error.to_string(),
+                }),
+            }
+        }
+        None
+    }
+
+    /// Loads the image called `name` from the packs, falling back to `default`.
+    fn load_texture(&mut self, name: &'static str, default: &[u8]) {
+        if let Some((path, bytes)) = self.replacement(name) {
+            match load_png_image_from_bytes(&bytes) {
+                Ok(image) => {
+                    self.images.insert(name, image);
+                    return;
+                }
+                Err(reason) => self.errors.push(AssetError::BadTexture {
+                    name,
+                    reason: format!("{}: {reason}", path.display()),
+                }),
+            }
+        }
+        match load_png_image_from_bytes(default) {
+            Ok(image) => {
+                self.images.insert(name, image);
+            }
+            Err(reason) => self.errors.push(AssetError::BadTexture { name, reason }),
+        }
+    }
+
+    /// Loads the sound called `name` from the packs, falling back to `default`.
+    async fn load_sound(&mut self, name: &'static str, default: &[u8]) {
+        if let Some((path, bytes)) = self.replacement(name) {
+            match load_sound(&bytes).await {
+                Ok(sound) => {
+                    self.sounds.insert(name, sound);
+                    return;
+                }
+                Err(reason) => self.errors.push(AssetError::BadSound {
+                    name,
+                    reason: format!("{}: {reason}", path.display()),
+                }),
+            }
+        }
+        match load_sound(default).await {
+            Ok(sound) => {
+                self.sounds.insert(name, sound);
+            }
+            Err(reason) => self.errors.push(AssetError::BadSound { name, reason }),
+        }
+    }
+
+    /// Loads the font called `name` from the packs, falling back to `default`.
+    fn load_font(&mut self, name: &'static str, default: &[u8]) {
+        if let Some((path, bytes)) = self.replacement(name) {
+            match load_ttf_font_from_bytes(&bytes) {
+                Ok(font) => {
+                    self.fonts.insert(name, font);
+                    return;
+                }
+                Err(error) => self.errors.push(AssetError::BadFont {
+                    name,
+                    reason: format!("{}: {error}", path.display()),
+                }),
+            }
+        }
+        match load_ttf_font_from_bytes(default) {
+            Ok(font) => {
+                self.fonts.insert(name, font);
+            }
+            Err(error) => self.errors.push(AssetError::BadFont {
+                name,
+                reason: error.to_string(),
+            }),
+        }
+    }
+
+    /// Loads the translated picture `asset`, if the packs have it.
+    /// Translations are full-screen pictures, so each gets a texture of its own.
+    fn load_translation(&mut self, asset: &TranslatedAsset) {
+        let Some((path, bytes)) = self.replacement(asset.name) else {
+            return;
+        };
+        match load_png_image_from_bytes(&bytes) {
+            Ok(image) => {
+                let region = TextureRegion::whole(texture_from_image(&image));
+                self.translations
+                    .push((asset.picture, asset.language, region));
+            }
+            Err(reason) => self.errors.push(AssetError::BadTexture {
+                name: asset.name,
+                reason: format!("{}: {reason}", path.display()),
+            }),
+        }
+    }
+
+    /// Packs the small images into atlases, and gives each big image a texture of its own.
+    /// Images that failed to load become a placeholder.
+    fn pack_textures(&mut self) {
+        let images: Vec<(&'static str, Image)> = TEXTURES
+            .iter()
+            .map(|asset| {
+                let image = self
+                    .images
+                    .remove(asset.name)
+                    .unwrap_or_else(placeholder_image);
+                (asset.name, image)
+            })
+            .collect();
+        // Big images (the full-screen pictures and the background) would fill an atlas
+        // on their own, and are drawn one at a time anyway.
+        let (small, big): (Vec<_>, Vec<_>) = images.iter().partition(|(_, image)| {
+            image.width <= ATLAS_MAX_SPRITE_SIZE && image.height <= ATLAS_MAX_SPRITE_SIZE
+        });
+        let small_images: Vec<&Image> = small.iter().map(|(_, image)| image).collect();
+        for ((name, _), region) in small.iter().zip(pack_atlases(&small_images)) {
+            self.textures.insert(name, region);
+        }
+        for (name, image) in big {
+            let region = TextureRegion::whole(texture_from_image(image));
+            self.textures.insert(name, region);
+        }
+    }
+
+    /// Takes the image called `name`, or a placeholder if there is none.
+    fn texture(&mut self, name: &str) -> TextureRegion {
+        self.textures
+            .remove(name)
+            .unwrap_or_else(|| TextureRegion::whole(texture_from_image(&placeholder_image())))
+    }
+
+    /// Takes the sound called `name`, or `None` (silence) if it failed to load.
+    fn sound(&mut self, name: &str) -> Option<Sound> {
+        self.sounds.remove(name)
+    }
+
+    /// Takes the font called `name`, or `None` (the built-in font) if it failed to load.
+    fn font(&mut self, name: &str) -> Option<Font> {
+        self.fonts.remove(name)
+    }
+}
+
+/// Asynchronously loads all game assets (textures, sounds and the font),
+/// showing a loading screen with a progress bar while they load.
+/// Assets that fail to load are replaced by placeholders (a checkerboard, silence, or the
+/// built-in font), and returned as errors alongside the assets, so the game can still start.
+/// `async fn` means this function can perform operations (like file loading)
+/// without blocking the main thread, important for responsiveness.
+/// `display`: The virtual screen to draw the loading screen on.
+/// `pack_directories`: The asset packs to use, in order. Later packs win.
+pub async fn load_assets(
+    display: &Display,
+    pack_directories: &[PathBuf],
+) -> (Assets, Vec<AssetError>) {
+    let total = TEXTURES.len() + SOUNDS.len() + FONTS.len();
+    let mut loader = Loader {
+        packs: Vec::new(),
+        images: HashMap::new(),
+        textures: HashMap::new(),
+        sounds: HashMap::new(),
+        fonts: HashMap::new(),
+        translations: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    // --- Read the Asset Packs ---
+    for directory in pack_directories {
+        match AssetPack::load(directory) {
+            Ok(pack) => {
+                // Point out names the game doesn't know, which are most likely typos.
+                for (name, _) in &pack.files {
+                    let known = TEXTURES
+                        .iter()
+                        .chain(SOUNDS.iter())
+                        .chain(FONTS.iter())
+                        .map(|asset| asset.name)
+                        .chain(TRANSLATED_TEXTURES.iter().map(|asset| asset.name))
+                        .any(|known_name| known_name == name);
+                    if !known {
+                        loader.errors.push(AssetError::UnknownAsset {
+                            directory: directory.clone(),
+                            name: name.clone(),
+                        });
+                    }
+                }
+                loader.packs.push(pack);
+            }
+            Err(error) => loader.errors.push(error),
+        }
+    }
+
+    // --- Load Everything ---
+    // Show the loading screen before each asset, so there is something on screen
+    // while the bigger images and the music are decoded.
+    for (done, asset) in TEXTURES.iter().enumerate() {
+        draw_loading_screen(display, done, total, asset.name);
+        next_frame().await;
+        loader.load_texture(asset.name, asset.bytes);
+    }
+    for (done, asset) in SOUNDS.iter().enumerate() {
+        draw_loading_screen(display, TEXTURES.len() + done, total, asset.name);
+        next_frame().await;
+        loader.load_sound(asset.name, asset.bytes).await;
+    }
+    for (done, asset) in FONTS.iter().enumerate() {
+        draw_loading_screen(
+            display,
+            TEXTURES.len() + SOUNDS.len() + done,
+            total,
+            asset.name,
+        );
+        next_frame().await;
+        loader.load_font(asset.name, asset.bytes);
+    }
+
+    // Only packs have translated pictures, so there is usually nothing to load here.
+    for asset in &TRANSLATED_TEXTURES {
+        loader.load_translation(asset);
+    }
+
+    // --- Pack the Atlases ---
+    loader.pack_textures();
+
+    // --- Hand Them Out ---
+    let assets = Assets {
+        player_right: loader.texture("player_right"),
+        player_left: loader.texture("player_left"),
+        platform: loader.texture("platform"),
+        choco_bar: loader.texture("choco_bar"),
+        platform_tile: loader.texture("platform_tile"),
+        cookie_tile: loader.texture("cookie_tile"),
+        choco_tile: loader.texture("choco_tile"),
+        chicken: loader.texture("chicken"),
+        spike: loader.texture("spike"),
+        egg: loader.texture("egg"),
+        // Game state screens
+        game_over: loader.texture("game_over"),
+        win: loader.texture("win"),
+        game_start: loader.texture("game_start"),
+        score_panel: loader.texture("score_panel"),
+        // Environment
+        cloud: loader.texture("cloud"),
+        house: loader.texture("house"),
+        background: loader.texture("background"),
+        // Load all meme textures into the array
+        meme_textures: [
+            loader.texture("meme1"),
+            loader.texture("meme2"),
+            loader.texture("meme3"),
+            loader.texture("meme4"),
+            loader.texture("meme5"),
+            loader.texture("meme6"),
+            loader.texture("meme7"),
+            loader.texture("meme8"),
+        ],
+        jump: loader.sound("jump"),
+        egg_collect: loader.sound("egg_collect"),
+        chicken_hit: loader.sound("chicken_hit"),
+        spike_hit: loader.sound("spike_hit"),
+        magic: loader.sound("magic"),
+        background_music: loader.sound("background_music"),
+        game_over_sound: loader.sound("game_over_sound"),
+        win_sound: loader.sound("win_sound"),
+        font: loader.font("font"),
+        translations: loader.translations,
+    };
+    (assets, loader.errors)
+}
@@ -0,0 +1,118 @@
+//! Texture atlases: many small images packed side by side into one big texture.
+//!
+//! Switching textures between draw calls is slow, and Macroquad can only batch draws that use
+//! the same texture. With the sprites packed into an atlas, drawing the level's platforms,
+//! eggs, spikes, chickens and players mostly uses a single texture. Each sprite is then a
+//! `TextureRegion`: the atlas plus the rectangle of it the sprite occupies.
+
+use crate::constants::*;
+use macroquad::prelude::*;
+
+/// A picture to draw: a rectangle of a texture, or the whole texture.
+#[derive(Clone)]
+pub struct TextureRegion {
+    /// The texture the picture is in.
+    pub texture: Texture2D,
+    /// Where the picture is in the texture, in pixels.
+    pub source: Rect,
+}
+
+impl TextureRegion {
+    /// A region covering all of `texture`, for images that aren't in an atlas.
+    pub fn whole(texture: Texture2D) -> TextureRegion {
+        let source = Rect::new(0.0, 0.0, texture.width(), texture.height());
+        TextureRegion { texture, source }
+    }
+
+    /// The picture's size in pixels.
+    pub fn size(&self) -> Vec2 {
+        self.source.size()
+    }
+
+    /// Draws the picture, just like `draw_texture_ex` draws a texture.
+    /// `params.source` is ignored: it's set to the picture's rectangle.
+    pub fn draw(&self, x: f32, y: f32, color: Color, params: DrawTextureParams) {
+        draw_texture_ex(
+            &self.texture,
+            x,
+            y,
+            color,
+            DrawTextureParams {
+                source: Some(self.source),
+                ..params
+            },
+        );
+    }
+}
+
+/// Packs images into as few atlases as possible, using "shelves": images are placed left to
+/// right along a row as tall as the tallest of them, and a new row starts when one is full.
+/// Sorting the images from tallest to shortest first keeps the wasted space small.
+/// Returns a region for each image, in the same order as `images`.
+/// `images`: The images to pack. Each must fit within `ATLAS_SIZE`.
+pub fn pack_atlases(images: &[&Image]) -> Vec<TextureRegion> {
+    let mut order: Vec<usize> = (0..images.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(images[index].height));
+
+    // --- Place the Images ---
+    // Work out which atlas each image goes in and where, before building any atlas.
+    let size = ATLAS_SIZE as u32;
+    let padding = ATLAS_PADDING as u32;
+    let mut placements = vec![(0, 0, 0); images.len()]; // (atlas, x, y) for each image
+    let mut atlas_count = 1;
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for &index in &order {
+        let (width, height) = (images[index].width as u32, images[index].height as u32);
+        if x + width > size {
+            // This shelf is full: start the next one below it.
+            (x, y, shelf_height) = (0, y + shelf_height, 0);
+        }
+        if y + height > size {
+            // This atlas is full: start another.
+            atlas_count += 1;
+            (x, y, shelf_height) = (0, 0, 0);
+        }
+        placements[index] = (atlas_count - 1, x, y);
+        // Leave a gap around each image, so neighbours never bleed into each other.
+        x += width + padding;
+        shelf_height = shelf_height.max(height + padding);
+    }
+
+    // --- Build the Atlases ---
+    let mut atlas_images: Vec<Image> = (0..atlas_count)
+        .map(|_| Image::gen_image_color(ATLAS_SIZE, ATLAS_SIZE, BLANK))
+        .collect();
+    for (image, &(atlas, x, y)) in images.iter().zip(&placements) {
+        copy_image(image, &mut atlas_images[atlas], x, y);
+    }
+    let textures: Vec<Texture2D> = atlas_images
+        .iter()
+        .map(|image| {
+            let texture = Texture2D::from_image(image);
+            // Set the texture filtering mode to Nearest. This prevents blurring in pixel art.
+            texture.set_filter(FilterMode::Nearest);
+            texture
+        })
+        .collect();
+
+    images
+        .iter()
+        .zip(&placements)
+        .map(|(image, &(atlas, x, y))| TextureRegion {
+            texture: textures[atlas].clone(),
+            source: Rect::new(x as f32, y as f32, image.width as f32, image.height as f32),
+        })
+        .collect()
+}
+
+/// Copies all of `image` into `atlas`, with its top-left corner at (`x`, `y`).
+fn copy_image(image: &Image, atlas: &mut Image, x: u32, y: u32) {
+    // Both images store 4 bytes (red, green, blue, alpha) per pixel, row by row.
+    let row_bytes = image.width as usize * 4;
+    let atlas_row_bytes = atlas.width as usize * 4;
+    for row in 0..image.height as usize {
+        let from = row * row_bytes;
+        let to = (y as usize + row) * atlas_row_bytes + x as usize * 4;
+        atlas.bytes[to..to + row_bytes].copy_from_slice(&image.bytes[from..from + row_bytes]);
+    }
+}
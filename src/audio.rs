@@ -0,0 +1,27 @@
+//! Playing music and sound effects.
+
+use crate::constants::*;
+use macroquad::audio::{play_sound, play_sound_once, PlaySoundParams, Sound};
+
+/// Plays a sound effect once, on top of whatever else is playing.
+/// `sound`: The sound effect to play, or `None` for a sound that failed to load (plays nothing).
+pub fn play_effect(sound: &Option<Sound>) {
+    if let Some(sound) = sound {
+        play_sound_once(sound);
+    }
+}
+
+/// Starts playing background music on loop.
+/// `sound`: The music to play, or `None` for music that failed to load (plays nothing).
+pub fn play_music(sound: &Option<Sound>) {
+    let Some(sound) = sound else {
+        return;
+    };
+    play_sound(
+        sound,
+        PlaySoundParams {
+            looped: true,         // Keep playing after it finishes
+            volume: MUSIC_VOLUME, // Play quieter than the sound effects
+        },
+    );
+}
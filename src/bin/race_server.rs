@@ -0,0 +1,17 @@
+// These lines disable certain warnings from Clippy, a Rust linter.
+// Useful for focusing on core logic, but good to address these in larger projects.
+#![allow(clippy::pedantic, clippy::nursery, clippy::manual_range_contains)]
+use easter_egg::constants::DEFAULT_SERVER_ADDRESS;
+use easter_egg::net::run_race_server;
+
+/// Runs a headless race server, with no window or sound, until it fails.
+/// The address to listen on can be given as the only argument, e.g. `race_server 0.0.0.0:7777`.
+fn main() {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_SERVER_ADDRESS.to_owned());
+    if let Err(error) = run_race_server(&address) {
+        eprintln!("The race server stopped: {error}");
+        std::process::exit(1);
+    }
+}
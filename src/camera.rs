@@ -0,0 +1,142 @@
+//! The camera that follows the players around the level.
+//!
+//! Snapping the view to the players every frame makes it jerk with every step and turn. Instead:
+//! - The players can move around a dead-zone in the middle of the view without moving it.
+//! - The view looks ahead in the direction the players face, easing over when they turn around.
+//! - The view follows the players up and down slowly, so jumping doesn't bob the screen.
+//! - The view never shows anything outside the level, on any side.
+//! - The view shakes for a moment when a player gets hurt.
+
+use crate::constants::*;
+use crate::display::Display;
+use macroquad::prelude::*;
+
+/// Moves the view smoothly after the players, and shakes it.
+pub struct CameraController {
+    /// The centre of the view in the world, before shaking.
+    center: Vec2,
+    /// The size of the view in the world. It grows when the players are too far apart to fit.
+    size: Vec2,
+    /// How far ahead of the players the view is looking right now (negative is to the left).
+    look_ahead: f32,
+    /// How much longer (in seconds) the view shakes for.
+    shake_time: f32,
+    /// How far (in pixels) the view moves while shaking, at the start of the shake.
+    shake_strength: f32,
+}
+
+impl CameraController {
+    /// Creates a camera already framing `focus`, so the first frame doesn't glide into place.
+    /// `focus`: The area around every player to keep in view.
+    /// `bounds`: The area of the level the view must stay inside.
+    pub fn new(focus: Rect, bounds: Rect) -> CameraController {
+        let size = CameraController::view_size(focus);
+        let mut camera = CameraController {
+            center: CameraController::target(focus, size),
+            size,
+            look_ahead: 0.0,
+            shake_time: 0.0,
+            shake_strength: 0.0,
+        };
+        camera.clamp(bounds);
+        camera
+    }
+
+    /// How big the view must be to fit `focus`: the size of the virtual screen, or bigger
+    /// (keeping its shape) when the players are too far apart to fit.
+    fn view_size(focus: Rect) -> Vec2 {
+        let width = (focus.w + 2.0 * CAMERA_PLAYER_MARGIN).max(VIRTUAL_WIDTH);
+        Vec2::new(width, width * VIRTUAL_HEIGHT / VIRTUAL_WIDTH)
+    }
+
+    /// Where the centre of the view should be to frame `focus`: centred on it horizontally,
+    /// with the players `CAMERA_FOCUS_HEIGHT` of the way down the view.
+    fn target(focus: Rect, size: Vec2) -> Vec2 {
+        let focus_center = focus.center();
+        Vec2::new(
+            focus_center.x,
+            focus_center.y - (CAMERA_FOCUS_HEIGHT - 0.5) * size.y,
+        )
+    }
+
+    /// Keeps the view inside `bounds`. When the view is bigger than the level, it shows the
+    /// start and the bottom of the level.
+    fn clamp(&mut self, bounds: Rect) {
+        let half = self.size / 2.0;
+        self.center.x = self
+            .center
+            .x
+            .min(bounds.right() - half.x)
+            .max(bounds.x + half.x);
+        self.center.y = self
+            .center
+            .y
+            .max(bounds.y + half.y)
+            .min(bounds.bottom() - half.y);
+    }
+
+    /// Moves the view towards the players. Call it once a frame, after the players move.
+    /// `focus`: The area around every player to keep in view.
+    /// `facing`: The direction the players face: 1.0 for right, -1.0 for left, 0.0 for neither.
+    /// `bounds`: The area of the level the view must stay inside.
+    /// `delta_time`: The time in seconds since the last frame update.
+    pub fn follow(&mut self, focus: Rect, facing: f32, bounds: Rect, delta_time: f32) {
+        self.size = CameraController::view_size(focus);
+        let target = CameraController::target(focus, self.size);
+
+        // --- Look-Ahead ---
+        // Ease the look-ahead towards the facing direction, so turning around pans over gently.
+        let look_ahead_target = facing * CAMERA_LOOK_AHEAD;
+        let step = CAMERA_LOOK_AHEAD_SPEED * delta_time;
+        self.look_ahead += (look_ahead_target - self.look_ahead).clamp(-step, step);
+
+        // --- Horizontal Dead-Zone ---
+        // Only move sideways once the point being looked at leaves the dead-zone, and then only
+        // far enough to bring it back to the dead-zone's edge.
+        let looking_at = target.x + self.look_ahead;
+        let half_zone = CAMERA_DEAD_ZONE_WIDTH / 2.0;
+        let wanted_x = looking_at.clamp(self.center.x - half_zone, self.center.x + half_zone);
+        // Catch up quickly but not instantly, so a respawn slides over instead of cutting.
+        let catch_up = (CAMERA_HORIZONTAL_DAMPING * delta_time).min(1.0);
+        self.center.x += (wanted_x - self.center.x) * catch_up;
+
+        // --- Vertical Follow ---
+        // Follow slowly up and down, so only climbing high (not each jump) moves the view.
+        let catch_up = (CAMERA_VERTICAL_DAMPING * delta_time).min(1.0);
+        self.center.y += (target.y - self.center.y) * catch_up;
+
+        self.clamp(bounds);
+
+        // --- Shake ---
+        self.shake_time = (self.shake_time - delta_time).max(0.0);
+    }
+
+    /// Shakes the view for `CAMERA_SHAKE_TIME` seconds, e.g. when a player gets hurt.
+    /// `strength`: How far (in pixels) the view moves while shaking. A weaker shake doesn't
+    /// cut a stronger one short.
+    pub fn shake(&mut self, strength: f32) {
+        if self.shake_time <= 0.0 || strength >= self.shake_strength {
+            self.shake_strength = strength;
+        }
+        self.shake_time = CAMERA_SHAKE_TIME;
+    }
+
+    /// The area of the world in view, including any shake.
+    pub fn view(&self) -> Rect {
+        // Wobble back and forth quickly, fading out as the shake ends. Two different speeds
+        // for x and y keep the wobble from looking like a straight line.
+        let fade = self.shake_time / CAMERA_SHAKE_TIME;
+        let phase = self.shake_time * CAMERA_SHAKE_SPEED;
+        let shake = Vec2::new(phase.sin(), (phase * 1.3).cos()) * self.shake_strength * fade;
+        let corner = self.center - self.size / 2.0 + shake;
+        Rect::new(corner.x, corner.y, self.size.x, self.size.y)
+    }
+
+    /// Sets up the camera for drawing the world, and returns the area of the world in view.
+    /// `display`: The virtual screen the world is drawn on.
+    pub fn apply(&self, display: &Display) -> Rect {
+        let view = self.view();
+        display.set_world_camera(view);
+        view
+    }
+}
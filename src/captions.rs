@@ -0,0 +1,94 @@
+//! Captions for sound effects, for players who can't hear them: a short description of each
+//! sound at the bottom of the screen, like "Egg collected", for a moment after it plays.
+
+use crate::assets::Assets;
+use crate::constants::*;
+use crate::locale::Strings;
+use crate::text::{draw_text_line, text_width, HUD_TEXT};
+use macroquad::prelude::*;
+
+/// The sounds that get a caption.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Cue {
+    Jump,
+    EggCollect,
+    ChickenHit,
+    SpikeHit,
+    Magic,
+    GameOver,
+    Win,
+}
+
+impl Cue {
+    /// The caption shown for the sound, in the language of `strings`.
+    pub fn label(self, strings: &Strings) -> &'static str {
+        strings.captions[self as usize]
+    }
+}
+
+/// The captions of the sounds that played recently.
+pub struct Captions {
+    /// Each caption, oldest first, with how much longer (in seconds) it stays on screen.
+    shown: Vec<(Cue, f32)>,
+}
+
+impl Captions {
+    /// Creates an empty list of captions.
+    pub fn new() -> Captions {
+        Captions { shown: Vec::new() }
+    }
+
+    /// Captions a sound that just played. Call it next to `play_effect`.
+    /// A sound that is already captioned stays on screen longer instead of showing twice.
+    pub fn show(&mut self, cue: Cue) {
+        self.shown.retain(|(shown, _)| *shown != cue);
+        self.shown.push((cue, CAPTION_TIME));
+        if self.shown.len() > MAX_CAPTIONS {
+            self.shown.remove(0);
+        }
+    }
+
+    /// Counts down how long the captions stay, and forgets the ones whose time is up.
+    /// `delta_time`: The time in seconds since the last frame update.
+    pub fn update(&mut self, delta_time: f32) {
+        for (_, time_left) in &mut self.shown {
+            *time_left -= delta_time;
+        }
+        self.shown.retain(|(_, time_left)| *time_left > 0.0);
+    }
+
+    /// Draws the captions centred across the screen, stacked upwards, newest at the bottom.
+    /// Expects the screen camera to be set.
+    /// `strings`: The words for the captions, in the chosen language.
+    /// `bottom`: Where the newest caption's box ends, usually the bottom of the screen.
+    pub fn draw(&self, assets: &Assets, strings: &Strings, bottom: f32) {
+        let line_height = TEXT_SIZE_SMALL * 1.5;
+        let mut baseline = bottom - line_height / 2.0;
+        for (cue, _) in self.shown.iter().rev() {
+            let text = cue.label(strings);
+            // A dark box behind each caption keeps it readable over anything.
+            let width = text_width(assets, text, TEXT_SIZE_SMALL) + TEXT_SIZE_SMALL;
+            draw_rectangle(
+                (VIRTUAL_WIDTH - width) / 2.0,
+                baseline - TEXT_SIZE_SMALL * 1.1,
+                width,
+                line_height,
+                CAPTION_BACKGROUND,
+            );
+            draw_text_line(
+                assets,
+                text,
+                VIRTUAL_WIDTH / 2.0,
+                baseline,
+                HUD_TEXT.centered(),
+            );
+            baseline -= line_height + 4.0;
+        }
+    }
+}
+
+impl Default for Captions {
+    fn default() -> Self {
+        Captions::new()
+    }
+}
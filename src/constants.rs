@@ -0,0 +1,325 @@
+//! The numbers that tune the game: physics, sizes, difficulty and timings.
+
+use macroquad::prelude::*;
+
+// --- Display Constants ---
+// The size (in pixels) of the virtual screen the game is drawn on, whatever the window's size.
+pub const VIRTUAL_WIDTH: f32 = 1024.0;
+pub const VIRTUAL_HEIGHT: f32 = 768.0;
+// The color of the bars around the virtual screen when the window's shape is different.
+pub const LETTERBOX_COLOR: Color = BLACK;
+
+// --- Physics Constants ---
+// Defines how quickly objects fall downwards (pixels per second squared).
+pub const GRAVITY: f32 = 1000.0;
+// A small buffer zone below the player to detect ground slightly before touching.
+pub const GROUND_DETECTION_BUFFER: f32 = 5.0;
+// A small margin subtracted from entity bounds for collision checks, can help prevent sticking.
+pub const COLLISION_MARGIN: f32 = 2.0;
+// The width and height of the cells the world is split into for finding nearby entities quickly.
+pub const GRID_CELL_SIZE: f32 = 256.0;
+
+// --- Player Constants ---
+// The starting position (x, y) of the player character on the screen.
+pub const PLAYER_START_POS: Vec2 = Vec2::new(243.0, 350.0);
+// How fast the player moves horizontally (pixels per second).
+pub const PLAYER_MOVEMENT_SPEED: f32 = 300.0;
+// The initial upward speed when the player jumps (pixels per second).
+pub const PLAYER_JUMP_SPEED: f32 = 500.0;
+// Tint for player 2's sprite in two-player games, so the players can tell themselves apart.
+pub const PLAYER_TWO_TINT: Color = Color::new(0.6, 0.8, 1.0, 1.0);
+// The furthest apart (in pixels) two players can get before the one in front is held back.
+pub const MAX_PLAYER_SPREAD: f32 = 1400.0;
+
+// --- Camera Constants ---
+// Space kept between the outermost players and the edges of the view.
+pub const CAMERA_PLAYER_MARGIN: f32 = 200.0;
+// How wide (in pixels) the area in the middle of the view is that the players can move around
+// in without moving the view.
+pub const CAMERA_DEAD_ZONE_WIDTH: f32 = 160.0;
+// How far (in pixels) ahead of the players the view looks, in the direction they face.
+pub const CAMERA_LOOK_AHEAD: f32 = 150.0;
+// How fast (in pixels per second) the look-ahead moves over when the players turn around.
+pub const CAMERA_LOOK_AHEAD_SPEED: f32 = 300.0;
+// How far down the view (0.0 = top, 1.0 = bottom) the players are kept when following them
+// up and down.
+pub const CAMERA_FOCUS_HEIGHT: f32 = 0.6;
+// How quickly the view catches up sideways and up and down (the share of the distance left
+// covered per second). Higher is snappier.
+pub const CAMERA_HORIZONTAL_DAMPING: f32 = 8.0;
+pub const CAMERA_VERTICAL_DAMPING: f32 = 3.0;
+// How high (in pixels) above its bottom the camera may show of a level.
+pub const LEVEL_HEIGHT: f32 = 1200.0;
+// How far (in pixels) the view moves when shaken by a player getting hurt.
+pub const CAMERA_HIT_SHAKE: f32 = 12.0;
+// How long (in seconds) a shake lasts, and how fast the view wobbles while it does.
+pub const CAMERA_SHAKE_TIME: f32 = 0.4;
+pub const CAMERA_SHAKE_SPEED: f32 = 60.0;
+
+// --- Chicken Constants ---
+// How close (in pixels) the player must get before a chicken reacts to them.
+pub const CHICKEN_SIGHT_RANGE: f32 = 350.0;
+// How fast a chicken flies while patrolling or heading for a perch (pixels per second).
+pub const CHICKEN_CRUISE_SPEED: f32 = 100.0;
+// How fast a chicken dives at the player (pixels per second).
+pub const CHICKEN_SWOOP_SPEED: f32 = 280.0;
+// The lowest a swooping chicken hovers (a y position), so it stays high enough to dive at the player.
+pub const CHICKEN_SWOOP_HOME_Y: f32 = 250.0;
+// How far below its home height a swooping chicken dives before climbing back up.
+pub const CHICKEN_SWOOP_DEPTH: f32 = 300.0;
+// How fast a frightened chicken flies away from the player (pixels per second).
+pub const CHICKEN_FLEE_SPEED: f32 = 220.0;
+// How long (in seconds) a chicken rests on a platform before looking for another one.
+pub const CHICKEN_PERCH_TIME: f32 = 3.0;
+// How far away (in pixels) a perching chicken looks for a platform to fly to.
+pub const CHICKEN_PERCH_RANGE: f32 = 800.0;
+// The area of the world chickens are allowed to fly in during story mode. They turn around at its edges.
+pub const CHICKEN_BOUNDS: Rect = Rect::new(0.0, 0.0, 5000.0, 800.0);
+// Relative chance of each behaviour being picked for a new chicken.
+// Order: wander, patrol, swoop, flee, perch.
+pub const CHICKEN_BEHAVIOUR_WEIGHTS: [u32; 5] = [4, 4, 5, 2, 5];
+
+// --- Platform Constants ---
+// How fast moving platforms travel (pixels per second).
+pub const MOVING_PLATFORM_SPEED: f32 = 80.0;
+// How far moving platforms travel away from their starting point before turning back.
+pub const MOVING_PLATFORM_RANGE: f32 = 150.0;
+// How long (in seconds) a crumbling platform shakes after being stood on before it falls.
+pub const CRUMBLE_DELAY: f32 = 0.6;
+// How long (in seconds) a fallen crumbling platform takes to reappear.
+pub const CRUMBLE_RESPAWN_TIME: f32 = 4.0;
+// The upward speed a bouncy platform launches the player with.
+pub const BOUNCY_PLATFORM_SPEED: f32 = 850.0;
+// A timed platform repeats a cycle of this many seconds...
+pub const TIMED_PLATFORM_PERIOD: f32 = 4.0;
+// ...and is solid for this many seconds of each cycle.
+pub const TIMED_PLATFORM_VISIBLE_TIME: f32 = 2.5;
+// Relative chance of each kind being picked for a floating platform.
+// Order: static, moving, crumbling, bouncy, timed.
+pub const PLATFORM_KIND_WEIGHTS: [u32; 5] = [10, 3, 3, 2, 2];
+
+// --- Tile Map Constants ---
+// The width and height of one terrain tile in the world (16x16 pixel art, Scaled by: 3.0).
+pub const TILE_SIZE: f32 = 48.0;
+// How many rows of tiles the terrain has, counting up from the bottom of the screen.
+pub const TILE_MAP_ROWS: usize = 8;
+// How many rows of solid ground a flat stretch of terrain has.
+pub const GROUND_HEIGHT: usize = 3;
+// The terrain starts and ends at these x positions in the world.
+pub const TERRAIN_START_X: f32 = -1024.0;
+pub const TERRAIN_END_X: f32 = 3600.0;
+// Terrain left of this x position is kept flat and safe so the player has room to start.
+pub const SAFE_START_X: f32 = 600.0;
+
+// --- Level Generation Constants ---
+// The average horizontal distance between floating platforms.
+pub const PLATFORM_SPACING: f32 = 50.0;
+// The x position of the house at the end of the level.
+pub const HOUSE_X: f32 = 3000.0;
+// How many different seeds the generator tries before repairing a level instead.
+pub const MAX_GENERATION_ATTEMPTS: u64 = 20;
+// The gap between the eggs laid on the ground when repairing a level. Small enough to fit
+// the most eggs the Custom difficulty can ask for between the start and the house.
+pub const REPAIR_EGG_SPACING: f32 = 100.0;
+// The generator only counts on this fraction of the player's full jump height and distance,
+// leaving some room for imperfect jumps.
+pub const JUMP_SAFETY_FACTOR: f32 = 0.8;
+
+// --- Endless Mode Constants ---
+// The width of each chunk of level generated ahead of the player in endless mode.
+pub const ENDLESS_CHUNK_WIDTH: f32 = 1500.0;
+// Every this many pixels travelled, endless mode gets one step harder...
+pub const ENDLESS_RAMP_DISTANCE: f32 = 6000.0;
+// ...up to this many times harder than at the start.
+pub const ENDLESS_MAX_RAMP: f32 = 3.0;
+// How far (in pixels) the player has to travel to earn one point in endless mode.
+pub const ENDLESS_DISTANCE_PER_POINT: f32 = 100.0;
+
+// --- Online Race Constants ---
+// The race server's address when none is given to `race_server` or with `--connect`.
+pub const DEFAULT_SERVER_ADDRESS: &str = "127.0.0.1:7777";
+// How many times a second the race server sends every client the race's state.
+pub const SERVER_TICK_RATE: f32 = 30.0;
+// The longest frame time (in seconds) the race server accepts for a single input.
+pub const MAX_INPUT_DELTA: f32 = 0.05;
+// The most time (in seconds) of play a client's inputs can build up on the race server,
+// for inputs that arrive in bunches after a slow moment on the network.
+pub const MAX_INPUT_BUDGET: f32 = 0.25;
+// How long (in seconds) to wait for the race server to answer when joining.
+pub const CONNECT_TIMEOUT: f32 = 2.0;
+// How many times the request to join is sent within that time, in case it gets lost.
+pub const CONNECT_ATTEMPTS: u32 = 4;
+// How long (in seconds) either side waits without hearing anything before giving up on the other.
+pub const CLIENT_TIMEOUT: f32 = 5.0;
+// The most racers a race server accepts at once.
+pub const MAX_RACE_PLAYERS: usize = 8;
+// The bottom of race levels. Every racer needs the same level, so it can't depend on window size.
+pub const RACE_LEVEL_BOTTOM: f32 = 768.0;
+// How quickly other racers slide towards their latest position from the server (per second).
+pub const REMOTE_PLAYER_SMOOTHING: f32 = 12.0;
+// The biggest UDP packet either side expects to receive, in bytes.
+pub const MAX_PACKET_SIZE: usize = 512;
+
+// --- Time Attack Constants ---
+// How many checkpoints split the way to the house into equal stretches.
+pub const CHECKPOINT_COUNT: usize = 3;
+// Extra time (in seconds) allowed for fetching each egg needed to win, on top of running to the house.
+pub const EGG_DETOUR_TIME: f32 = 2.5;
+// How many times longer than the ideal run each medal allows: gold, silver, bronze.
+pub const MEDAL_TIME_FACTORS: [f32; 3] = [1.5, 2.0, 3.0];
+// How long (in seconds) a split time stays on screen after passing a checkpoint.
+pub const SPLIT_DISPLAY_TIME: f32 = 3.0;
+// The colours of the gold, silver and bronze medals.
+pub const MEDAL_COLORS: [Color; 3] = [GOLD, LIGHTGRAY, BROWN];
+
+// --- Combat Constants ---
+// The upward speed the player bounces with after stomping on a chicken.
+pub const STOMP_BOUNCE_SPEED: f32 = 450.0;
+// How long (in seconds) a chicken flashes and can't be hurt again after being stomped.
+pub const CHICKEN_HIT_COOLDOWN: f32 = 0.4;
+// Hit points of a normal chicken and of the tougher (tinted) variant.
+pub const CHICKEN_HEALTH: u32 = 1;
+pub const TOUGH_CHICKEN_HEALTH: u32 = 2;
+// Percentage of chickens that spawn as the tougher variant.
+pub const TOUGH_CHICKEN_CHANCE: i32 = 25;
+// The tint used to tell tough chickens apart from normal ones.
+pub const TOUGH_CHICKEN_TINT: Color = Color::new(1.0, 0.6, 0.6, 1.0);
+// How fast a defeated chicken spins while it falls out of the level (radians per second).
+pub const CHICKEN_DEATH_SPIN: f32 = 8.0;
+
+// --- Score Constants ---
+// Points awarded for collecting an egg.
+pub const EGG_POINTS: u32 = 1;
+// Points awarded for defeating a chicken (scaled by its hit points).
+pub const CHICKEN_POINTS: u32 = 3;
+
+// --- Entity Sizes ---
+// Dimensions (width, height) for various game objects.
+// Calculated by multiplying original pixel art size by a scaling factor.
+pub const PLAYER_SIZE: Vec2 = Vec2::new(30.0, 48.0); // Original: 10x16 pixels, Scaled by: 3.0
+pub const PLATFORM_BAR_SIZE: Vec2 = Vec2::new(214.5, 70.5); // Original: 143x47 pixels, Scaled by: 1.5
+pub const CHOCO_BAR_SIZE: Vec2 = Vec2::new(176.0, 16.0); // Original: 44x4 pixels, Scaled by: 4.0
+pub const PLATFORM_TILE_SIZE: Vec2 = Vec2::new(48.0, 48.0); // Original: 16x16 pixels, Scaled by: 3.0
+pub const CHOCO_TILE_SIZE: Vec2 = Vec2::new(48.0, 39.0); // Original: 16x13 pixels, Scaled by: 3.0
+pub const COOKIE_TILE_SIZE: Vec2 = Vec2::new(48.0, 42.0); // Original: 16x14 pixels, Scaled by: 3.0
+pub const CHICKEN_SIZE: Vec2 = Vec2::new(52.0, 48.0); // Original: 13x12 pixels, Scaled by: 4.0
+pub const EGG_SIZE: Vec2 = Vec2::new(40.0, 40.0); // Original: 400x400 pixels, Scaled by: 0.1
+pub const SPIKE_SIZE: Vec2 = Vec2::new(60.0, 52.0); // Original: 15x13 pixels, Scaled by: 4.0
+pub const HOUSE_SIZE: Vec2 = Vec2::new(423.0, 624.0); // Original: 141x208 pixels, Scaled by: 3.0
+pub const CLOUD_SIZE: Vec2 = Vec2::new(786.0, 150.0); // Original: 262x50 pixels, Scaled by: 3.0
+pub const BACKGROUND_SIZE: Vec2 = Vec2::new(1024.0, 2304.0); // Original: 1024x2304 pixels, Scaled by: 1.0
+
+// --- Game Goal Constants ---
+// These are the values used by the Normal difficulty; other difficulties use their own.
+// How many eggs the player needs to collect to trigger the "End" state (reaching the house).
+pub const EGGS_NEEDED_FOR_HOUSE: u32 = 2;
+// How many eggs the player needs to collect to trigger the "Win" state (reaching the house with enough eggs).
+pub const EGGS_NEEDED_FOR_WIN: u32 = 5;
+// How long (in seconds) the player can't be hurt after losing a life.
+pub const RESPAWN_INVULNERABILITY: f32 = 2.0;
+// The file the high-score table is saved to, next to the game.
+pub const HIGH_SCORES_FILE: &str = "high_scores.txt";
+// How many scores the high-score table keeps for each difficulty.
+pub const HIGH_SCORES_PER_DIFFICULTY: usize = 5;
+// The file the player's settings (like the language) are saved to, next to the game.
+pub const SETTINGS_FILE: &str = "settings.txt";
+
+// --- Asset Constants ---
+// The file in each asset pack directory that lists which assets the pack replaces.
+pub const ASSET_MANIFEST_FILE: &str = "manifest.txt";
+// How often (in seconds) the `hot-reload` feature looks for changed asset files.
+pub const HOT_RELOAD_INTERVAL: f64 = 0.5;
+
+// The width and height of each texture atlas the sprites are packed into.
+pub const ATLAS_SIZE: u16 = 1024;
+// Images wider or taller than this get a texture of their own instead of going in an atlas.
+pub const ATLAS_MAX_SPRITE_SIZE: u16 = 512;
+// The gap (in pixels) left between images in an atlas.
+pub const ATLAS_PADDING: u16 = 2;
+
+// --- Audio Constants ---
+// How loud the background music plays (0.0 to 1.0), leaving room for the sound effects.
+pub const MUSIC_VOLUME: f32 = 0.5;
+
+// --- Scene Constants ---
+// How long (in seconds) a fade or wipe between scenes takes: half to cover the screen, half to uncover it.
+pub const TRANSITION_TIME: f32 = 0.6;
+// The color the screen fades or wipes to between scenes.
+pub const TRANSITION_COLOR: Color = BLACK;
+// The translucent shade drawn over the game while it is paused.
+pub const PAUSE_SHADE: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.6,
+};
+
+// --- Particle Constants ---
+// The most particles in flight at once. Bursts past this are skipped.
+pub const MAX_PARTICLES: usize = 2000;
+// How fast (pixels per second) the player must be falling for landing to kick up dust.
+pub const LANDING_DUST_SPEED: f32 = 200.0;
+// How long (in seconds) confetti keeps being thrown after a win, and how often.
+pub const CONFETTI_TIME: f32 = 3.0;
+pub const CONFETTI_INTERVAL: f32 = 0.25;
+// The colors of each kind of particle.
+pub const EGG_SPARKLE_COLORS: [Color; 3] = [GOLD, YELLOW, WHITE];
+pub const DUST_COLORS: [Color; 2] = [
+    Color::new(0.75, 0.65, 0.5, 0.8),
+    Color::new(0.6, 0.5, 0.4, 0.8),
+];
+pub const FEATHER_COLORS: [Color; 2] = [WHITE, Color::new(0.95, 0.9, 0.8, 1.0)];
+pub const CONFETTI_COLORS: [Color; 5] = [RED, ORANGE, YELLOW, GREEN, SKYBLUE];
+
+// --- Text Constants ---
+// The heights (in virtual screen pixels) of small text (the HUD), normal text (menus and
+// results), large text (the timer) and headings.
+pub const TEXT_SIZE_SMALL: f32 = 24.0;
+pub const TEXT_SIZE: f32 = 28.0;
+pub const TEXT_SIZE_LARGE: f32 = 38.0;
+pub const TEXT_SIZE_TITLE: f32 = 60.0;
+// How thick (in pixels) text outlines are, and how far down and right text shadows fall.
+pub const TEXT_OUTLINE_WIDTH: f32 = 2.0;
+pub const TEXT_SHADOW_OFFSET: f32 = 2.0;
+// The colors of text outlines and shadows.
+pub const TEXT_OUTLINE_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.8);
+pub const TEXT_SHADOW_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+
+// --- Accessibility Constants ---
+// The outline drawn around spikes and chickens in high-contrast mode: a thick dark line with
+// a thin bright one on top, so it stands out against light and dark backgrounds alike.
+pub const HAZARD_OUTLINE_COLORS: [Color; 2] = [BLACK, YELLOW];
+pub const HAZARD_OUTLINE_WIDTHS: [f32; 2] = [5.0, 2.0];
+// How fast the clouds drift with reduced motion, compared to normal.
+pub const REDUCED_MOTION_DRIFT: f32 = 0.25;
+// The slowest game speed the player can pick, and how much each step changes it.
+pub const MIN_GAME_SPEED: f32 = 0.5;
+pub const GAME_SPEED_STEP: f32 = 0.1;
+// How long (in seconds) a caption stays on screen after its sound plays,
+// and the most captions shown at once.
+pub const CAPTION_TIME: f32 = 2.0;
+pub const MAX_CAPTIONS: usize = 3;
+// The box behind each caption, so it can be read over anything.
+pub const CAPTION_BACKGROUND: Color = Color::new(0.0, 0.0, 0.0, 0.7);
+
+// --- Visual Constants ---
+// How far outside the view (in pixels) entities are still drawn, for sprites that shake or spin
+// a little past their rectangle.
+pub const CULL_MARGIN: f32 = 64.0;
+// The background color of the game window (a light beige).
+pub const BACKGROUND_COLOR: Color = Color {
+    r: 0.92, // Red component (0.0 to 1.0)
+    g: 0.88, // Green component (0.0 to 1.0)
+    b: 0.78, // Blue component (0.0 to 1.0)
+    a: 1.0,  // Alpha (transparency) component (1.0 is fully opaque)
+};
+// The text and outline color of the loading screen.
+pub const LOADING_TEXT_COLOR: Color = DARKBROWN;
+// The color the loading screen's progress bar fills with.
+pub const LOADING_BAR_COLOR: Color = ORANGE;
+// The two colors of the checkerboard drawn in place of images that failed to load.
+pub const PLACEHOLDER_COLORS: [Color; 2] = [MAGENTA, BLACK];
+// Tint for the far clouds, fading them into the sky so they look further away.
+pub const FAR_CLOUD_TINT: Color = Color::new(1.0, 1.0, 1.0, 0.6);
+// Tint for the clouds passing in front of the level, faint enough to see the level through.
+pub const FOREGROUND_TINT: Color = Color::new(1.0, 1.0, 1.0, 0.35);
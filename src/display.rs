@@ -0,0 +1,104 @@
+//! The virtual screen the game is drawn on.
+//!
+//! The game is always drawn at the same size, `VIRTUAL_WIDTH` by `VIRTUAL_HEIGHT` pixels, into
+//! an offscreen texture (a "render target"). At the end of each frame that texture is scaled up
+//! to fill the window, with bars around it where the window's shape is different. This way the
+//! level, the physics and the HUD are the same at any window size, and only the final picture is
+//! scaled.
+
+use crate::constants::*;
+use macroquad::camera::{set_camera, set_default_camera, Camera2D};
+use macroquad::prelude::*;
+
+/// How the virtual screen is scaled up to fill the window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scaling {
+    /// As big as fits, keeping its shape. Fills more of the window, but at sizes that aren't
+    /// a whole multiple of the virtual screen some pixels come out a little wider than others.
+    Letterbox,
+    /// The biggest whole multiple (2x, 3x, ...) that fits, so every pixel of the art becomes
+    /// a square of exactly the same size. Windows smaller than the virtual screen fall back to
+    /// `Letterbox`.
+    PixelPerfect,
+}
+
+/// The offscreen texture the game is drawn on, and how it's put on the window.
+pub struct Display {
+    /// The texture everything is drawn into.
+    target: RenderTarget,
+    /// How the texture is scaled up to fill the window.
+    pub scaling: Scaling,
+}
+
+impl Display {
+    /// Creates the virtual screen.
+    /// `scaling`: How to scale it up to fill the window.
+    pub fn new(scaling: Scaling) -> Display {
+        let target = render_target(VIRTUAL_WIDTH as u32, VIRTUAL_HEIGHT as u32);
+        // Set the texture filtering mode to Nearest. This keeps pixel art sharp when scaled up.
+        target.texture.set_filter(FilterMode::Nearest);
+        Display { target, scaling }
+    }
+
+    /// Points the camera at `view`, an area of the world, drawn onto the whole virtual screen.
+    /// `view`: The area of the world to show.
+    pub fn set_world_camera(&self, view: Rect) {
+        // Create a 2D camera. `from_display_rect` sets up the view area.
+        let mut camera = Camera2D::from_display_rect(view);
+        // By default, Macroquad's Y-axis points down. Games often use Y-axis pointing up.
+        // Flipping the camera's Y-zoom effectively inverts the Y-axis for drawing.
+        camera.zoom.y = -camera.zoom.y;
+        // Note: This also means positive Y velocity moves things *up* the screen,
+        // and gravity needs to be positive to pull things *down*.
+
+        // Draw into the virtual screen instead of the window.
+        camera.render_target = Some(self.target.clone());
+        set_camera(&camera);
+    }
+
+    /// Sets up the camera for drawing menus and the HUD: (0, 0) is the top-left corner of the
+    /// virtual screen and (`VIRTUAL_WIDTH`, `VIRTUAL_HEIGHT`) the bottom-right corner.
+    pub fn set_screen_camera(&self) {
+        self.set_world_camera(Rect::new(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT));
+    }
+
+    /// Where on the window the virtual screen goes, in window pixels.
+    fn placement(&self) -> Rect {
+        let fit = (screen_width() / VIRTUAL_WIDTH).min(screen_height() / VIRTUAL_HEIGHT);
+        let scale = match self.scaling {
+            Scaling::PixelPerfect if fit >= 1.0 => fit.floor(),
+            Scaling::PixelPerfect | Scaling::Letterbox => fit,
+        };
+        let (width, height) = (VIRTUAL_WIDTH * scale, VIRTUAL_HEIGHT * scale);
+        // Centre it, on whole pixels so the art isn't smeared across two.
+        Rect::new(
+            ((screen_width() - width) / 2.0).round(),
+            ((screen_height() - height) / 2.0).round(),
+            width,
+            height,
+        )
+    }
+
+    /// Starts a frame: everything drawn until `present` goes onto the virtual screen.
+    pub fn begin(&self) {
+        self.set_screen_camera();
+    }
+
+    /// Ends a frame: draws the virtual screen onto the window, scaled up, with bars around it.
+    pub fn present(&self) {
+        // Switch back to drawing on the window itself.
+        set_default_camera();
+        clear_background(LETTERBOX_COLOR);
+        let placement = self.placement();
+        draw_texture_ex(
+            &self.target.texture,
+            placement.x,
+            placement.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(placement.size()),
+                ..DrawTextureParams::default()
+            },
+        );
+    }
+}
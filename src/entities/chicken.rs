@@ -0,0 +1,398 @@
+//! The chickens: enemies with their own behaviours that can be stomped.
+
+use crate::constants::*;
+use crate::entities::platform::{platforms_in, PlatformKind};
+use crate::entities::weighted_index;
+use crate::world::components::{
+    Behaviour, Collider, Hazard, Layer, Sprite, SpriteImage, Transform,
+};
+use crate::world::{Entity, World};
+use macroquad::prelude::*;
+use macroquad::rand::{gen_range, ChooseRandom};
+
+/// The different ways a chicken can move around the level.
+/// Each chicken picks one behaviour when it is created and keeps it for the whole game.
+pub enum ChickenBehaviour {
+    /// Flies in a straight line, bouncing off the world edges and platforms.
+    Wander,
+    /// Flies back and forth between two points.
+    Patrol {
+        /// One end of the patrol route.
+        start: Vec2,
+        /// The other end of the patrol route.
+        end: Vec2,
+        /// `true` while flying towards `end`, `false` while flying back to `start`.
+        heading_to_end: bool,
+    },
+    /// Hovers at a fixed height and dives at the player when they pass underneath.
+    Swoop {
+        /// The height the chicken hovers at and returns to after a dive.
+        home_y: f32,
+        /// `true` while the chicken is diving.
+        diving: bool,
+    },
+    /// Drifts around like `Wander`, but flies away when the player gets close.
+    Flee,
+    /// Rests on top of platforms, flying to another one when disturbed or bored.
+    /// Wanders while there is no platform to rest on nearby.
+    Perch {
+        /// The platform the chicken is on or heading to.
+        target: Option<Entity>,
+        /// Seconds left to rest. Zero while the chicken is flying.
+        rest_timer: f32,
+    },
+}
+
+impl ChickenBehaviour {
+    /// Picks a random behaviour for a chicken spawned at `spawn`,
+    /// using `weights` (wander, patrol, swoop, flee, perch) as relative chances.
+    pub fn choose(spawn: Vec2, weights: [u32; 5]) -> ChickenBehaviour {
+        match weighted_index(&weights) {
+            0 => ChickenBehaviour::Wander,
+            1 => {
+                // Patrol a horizontal stretch around the spawn point.
+                let half_width = gen_range(150.0, 400.0);
+                ChickenBehaviour::Patrol {
+                    start: Vec2::new(spawn.x - half_width, spawn.y),
+                    end: Vec2::new(spawn.x + half_width, spawn.y + gen_range(-100.0, 100.0)),
+                    heading_to_end: true,
+                }
+            }
+            2 => ChickenBehaviour::Swoop {
+                // Swooping chickens hover high up so they can dive at the player.
+                home_y: spawn.y.min(CHICKEN_SWOOP_HOME_Y),
+                diving: false,
+            },
+            3 => ChickenBehaviour::Flee,
+            _ => ChickenBehaviour::Perch {
+                target: None,
+                rest_timer: 0.0,
+            },
+        }
+    }
+}
+
+/// The behaviour of a flying enemy. A chicken's position is in its transform, its speed in its
+/// velocity, and its health in its hazard, since chickens can be stomped.
+pub struct Chicken {
+    /// How this chicken decides where to fly.
+    pub behaviour: ChickenBehaviour,
+    /// Multiplier for how fast the chicken moves. Set by the difficulty.
+    pub speed_scale: f32,
+    /// The area the chicken is allowed to fly in. It turns around at the edges.
+    pub bounds: Rect,
+}
+
+/// What a chicken needs to know about a platform: where it is, whether it can rest on it,
+/// and whether it blocks the way.
+struct PlatformInfo {
+    entity: Entity,
+    rect: Rect,
+    perchable: bool,
+    solid: bool,
+}
+
+/// Creates a chicken centered on `center`.
+/// Chickens are made of a transform, a velocity, a sprite, a collider,
+/// a stompable hazard and a chicken behaviour.
+/// `velocity`: The chicken's starting velocity, before the chicken's speed scale.
+/// `health`: How many stomps it takes to defeat the chicken.
+pub fn spawn_chicken(
+    world: &mut World,
+    center: Vec2,
+    velocity: Vec2,
+    health: u32,
+    chicken: Chicken,
+) -> Entity {
+    let entity = world.spawn();
+    world.place(
+        entity,
+        Transform::new(Rect {
+            x: center.x - CHICKEN_SIZE.x / 2.0, // Center horizontally
+            y: center.y - CHICKEN_SIZE.y / 2.0, // Center vertically
+            w: CHICKEN_SIZE.x,
+            h: CHICKEN_SIZE.y,
+        }),
+    );
+    // Faster chickens simply cover more ground each second.
+    world
+        .velocities
+        .insert(entity, velocity * chicken.speed_scale);
+    let mut sprite = Sprite::new(SpriteImage::Chicken, Layer::Enemies);
+    // Tough chickens are tinted so players know to stomp them more than once.
+    if health > CHICKEN_HEALTH {
+        sprite.tint = TOUGH_CHICKEN_TINT;
+    }
+    world.sprites.insert(entity, sprite);
+    world.colliders.insert(entity, Collider::DEFAULT);
+    world.hazards.insert(
+        entity,
+        Hazard::Stompable {
+            health,
+            max_health: health,
+            hit_cooldown: 0.0,
+        },
+    );
+    world.behaviours.insert(entity, Behaviour::Chicken(chicken));
+    entity
+}
+
+/// Runs every chicken's behaviour for one frame, after the movement system has moved them:
+/// keeps them inside their bounds and out of platforms, decides where they fly next, and
+/// removes defeated chickens once they have fallen out of the level.
+/// `player_centers`: Where the players still in the game are. Each chicken reacts to the closest.
+/// `delta_time`: The time in seconds since the last frame update.
+pub fn chicken_system(world: &mut World, player_centers: &[Vec2], delta_time: f32) {
+    // --- Find Nearby Platforms ---
+    // Look up the platforms near each chicken first,
+    // since the platforms can't be read while the chickens' behaviours are being changed.
+    let nearby: Vec<(Entity, Vec<PlatformInfo>)> = world
+        .behaviours
+        .iter()
+        .filter_map(|(entity, behaviour)| {
+            let Behaviour::Chicken(chicken) = behaviour else {
+                return None;
+            };
+            let rect = world.transforms.get(entity)?.rect;
+            let area = match chicken.behaviour {
+                // Perching chickens look around for a platform to fly to.
+                ChickenBehaviour::Perch { .. } => Rect::new(
+                    rect.center().x - CHICKEN_PERCH_RANGE,
+                    rect.center().y - CHICKEN_PERCH_RANGE,
+                    2.0 * CHICKEN_PERCH_RANGE,
+                    2.0 * CHICKEN_PERCH_RANGE,
+                ),
+                // Everyone else only cares about the platforms they fly into.
+                _ => rect,
+            };
+            let platforms = platforms_in(world, area)
+                .map(|(entity, rect, platform)| PlatformInfo {
+                    entity,
+                    rect,
+                    perchable: matches!(platform.kind, PlatformKind::Static),
+                    solid: platform.is_solid(),
+                })
+                .collect();
+            Some((entity, platforms))
+        })
+        .collect();
+
+    let mut gone = Vec::new();
+    for (entity, platforms) in nearby {
+        let Some(Behaviour::Chicken(chicken)) = world.behaviours.get_mut(entity) else {
+            continue;
+        };
+        let (Some(transform), Some(velocity), Some(hazard)) = (
+            world.transforms.get_mut(entity),
+            world.velocities.get_mut(entity),
+            world.hazards.get(entity),
+        ) else {
+            continue;
+        };
+
+        let center = transform.rect.center();
+        let closest_player = player_centers
+            .iter()
+            .copied()
+            .min_by(|a, b| a.distance(center).total_cmp(&b.distance(center)))
+            .unwrap_or(center);
+        if hazard.is_defeated() {
+            // Defeated chickens ignore their behaviour and just spin and fall out of the level.
+            velocity.y += GRAVITY * delta_time;
+            transform.rotation += CHICKEN_DEATH_SPIN * delta_time;
+            // Forget about chickens that have fallen out of the level.
+            if transform.rect.y > chicken.bounds.bottom() + 200.0 {
+                gone.push(entity);
+            }
+        } else {
+            chicken.update(transform, velocity, closest_player, &platforms, delta_time);
+        }
+        world.grid.update(entity, transform.rect);
+
+        // Flash while hurt, and turn upside down once defeated.
+        if let Some(sprite) = world.sprites.get_mut(entity) {
+            // Blink by hiding the chicken every other 1/20th of a second.
+            let blink_hidden = match hazard {
+                Hazard::Stompable { hit_cooldown, .. } => {
+                    *hit_cooldown > 0.0 && ((*hit_cooldown * 20.0) as i32) % 2 == 0
+                }
+                Hazard::Sharp => false,
+            };
+            sprite.tint.a = if blink_hidden { 0.3 } else { 1.0 };
+            sprite.flip_y = hazard.is_defeated();
+        }
+    }
+    for entity in gone {
+        world.despawn(entity);
+    }
+}
+
+impl Chicken {
+    /// Runs the chicken's behaviour for one frame, after it has moved.
+    /// `transform`: The chicken's position and size.
+    /// `velocity`: The chicken's velocity, set for the next frame.
+    /// `player_center`: Where the closest player is, so chickens can chase or avoid them.
+    /// `platforms`: The platforms near the chicken, used for perching and bouncing.
+    /// `delta_time`: The time in seconds since the last frame update.
+    fn update(
+        &mut self,
+        transform: &mut Transform,
+        velocity: &mut Vec2,
+        player_center: Vec2,
+        platforms: &[PlatformInfo],
+        delta_time: f32,
+    ) {
+        // --- Stay inside the world ---
+        // Reverse direction if the chicken leaves the area it is allowed to fly in.
+        let rect = &mut transform.rect;
+        let bounds = self.bounds;
+        if rect.x > bounds.right() || rect.x < bounds.x {
+            velocity.x = -velocity.x;
+            rect.x = rect.x.clamp(bounds.x, bounds.right());
+        }
+        if rect.y > bounds.bottom() || rect.y < bounds.y {
+            velocity.y = -velocity.y;
+            rect.y = rect.y.clamp(bounds.y, bounds.bottom());
+        }
+
+        // --- Bounce off platforms ---
+        // Free-flying chickens shouldn't pass through platforms, so they bounce off vertically.
+        // That includes perching chickens wandering about with nowhere to perch.
+        let free_flying = match self.behaviour {
+            ChickenBehaviour::Wander | ChickenBehaviour::Flee => true,
+            ChickenBehaviour::Perch {
+                target: None,
+                rest_timer,
+            } => rest_timer <= 0.0,
+            _ => false,
+        };
+        if free_flying
+            && platforms
+                .iter()
+                .any(|platform| platform.solid && platform.rect.overlaps(rect))
+        {
+            // Undo this frame's vertical movement and reverse it.
+            rect.y -= velocity.y * delta_time;
+            velocity.y = -velocity.y;
+        }
+
+        let center = rect.center();
+        let height = rect.h;
+        let to_player = player_center - center;
+        let player_in_range = to_player.length() < CHICKEN_SIGHT_RANGE;
+        let cruise_speed = CHICKEN_CRUISE_SPEED * self.speed_scale;
+
+        // --- Decide on a velocity for the next frame ---
+        match &mut self.behaviour {
+            ChickenBehaviour::Wander => {}
+            ChickenBehaviour::Patrol {
+                start,
+                end,
+                heading_to_end,
+            } => {
+                // Turn around once we are close enough to the current end of the route.
+                let current_target = if *heading_to_end { *end } else { *start };
+                if center.distance(current_target) < 10.0 {
+                    *heading_to_end = !*heading_to_end;
+                }
+                let target = if *heading_to_end { *end } else { *start };
+                *velocity = (target - center).normalize_or_zero() * cruise_speed;
+            }
+            ChickenBehaviour::Swoop { home_y, diving } => {
+                if *diving {
+                    // Pull out of the dive once deep enough, then climb back home.
+                    if center.y > *home_y + CHICKEN_SWOOP_DEPTH {
+                        *diving = false;
+                        velocity.y = -cruise_speed;
+                    }
+                } else if player_in_range && to_player.y > 0.0 {
+                    // The player is below us and close enough: dive straight at them.
+                    *diving = true;
+                    *velocity =
+                        to_player.normalize_or_zero() * CHICKEN_SWOOP_SPEED * self.speed_scale;
+                } else {
+                    // Hover: drift sideways and ease back towards the home height.
+                    let drift = if velocity.x < 0.0 { -1.0 } else { 1.0 };
+                    velocity.x = drift * cruise_speed * 0.5;
+                    velocity.y = (*home_y - center.y) * 2.0 * self.speed_scale;
+                }
+            }
+            ChickenBehaviour::Flee => {
+                if player_in_range {
+                    // Fly directly away from the player.
+                    let away = -to_player.normalize_or_zero();
+                    *velocity = away * CHICKEN_FLEE_SPEED * self.speed_scale;
+                } else if velocity.length() > cruise_speed {
+                    // Calm down to cruising speed once the player is out of sight.
+                    *velocity = velocity.normalize() * cruise_speed;
+                }
+            }
+            ChickenBehaviour::Perch { target, rest_timer } => {
+                if *rest_timer > 0.0 {
+                    // Resting: stay still until the timer runs out or the player gets too close.
+                    *rest_timer -= delta_time;
+                    *velocity = Vec2::ZERO;
+                    if player_in_range {
+                        *rest_timer = 0.0;
+                        *target = None;
+                    }
+                }
+
+                if *rest_timer <= 0.0 {
+                    // Pick a new platform to fly to if we don't have one yet,
+                    // or the one we were heading to has been removed.
+                    let platform = target.and_then(|target| {
+                        platforms.iter().find(|platform| platform.entity == target)
+                    });
+                    if platform.is_none() {
+                        *target = choose_perch(platforms, center, player_center);
+                    }
+                    let platform = target.and_then(|target| {
+                        platforms.iter().find(|platform| platform.entity == target)
+                    });
+                    if let Some(platform) = platform {
+                        // Aim for the middle of the platform's top surface.
+                        let perch =
+                            Vec2::new(platform.rect.center().x, platform.rect.y - height / 2.0);
+                        // This frame's step may have carried us up to one step past the perch
+                        // (more on long frames), so anything within a step counts as arriving.
+                        let step = (cruise_speed * delta_time).max(5.0);
+                        if center.distance(perch) <= step {
+                            // Landed: settle exactly on the perch and rest for a while.
+                            rect.move_to(perch - rect.size() / 2.0);
+                            *rest_timer = CHICKEN_PERCH_TIME;
+                            *target = None;
+                            *velocity = Vec2::ZERO;
+                        } else {
+                            *velocity = (perch - center).normalize_or_zero() * cruise_speed;
+                        }
+                    } else if *velocity == Vec2::ZERO {
+                        // Nowhere to perch nearby: wander off in a random direction
+                        // (and keep looking for a perch on the way).
+                        *velocity =
+                            Vec2::from_angle(gen_range(0.0, std::f32::consts::TAU)) * cruise_speed;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Chooses a platform for a perching chicken to fly to.
+/// Prefers static platforms near the chicken that are not close to the player.
+/// Returns the chosen platform, or `None` if there is nowhere to go.
+fn choose_perch(platforms: &[PlatformInfo], from: Vec2, player_center: Vec2) -> Option<Entity> {
+    let candidates: Vec<Entity> = platforms
+        .iter()
+        .filter(|platform| {
+            let rect = platform.rect;
+            let top = Vec2::new(rect.center().x, rect.y);
+            // Only perch on platforms that stay put, close enough to fly to, but away from the player.
+            platform.perchable
+                && top.distance(from) < CHICKEN_PERCH_RANGE
+                && top.distance(player_center) > CHICKEN_SIGHT_RANGE
+        })
+        .map(|platform| platform.entity)
+        .collect();
+    candidates.choose().copied()
+}
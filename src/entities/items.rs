@@ -0,0 +1,76 @@
+//! The things lying around a level: eggs to collect, spikes to avoid and the house to reach.
+
+use crate::constants::*;
+use crate::world::components::{Collider, Hazard, Layer, Pickup, Sprite, SpriteImage, Transform};
+use crate::world::{Entity, World};
+use macroquad::prelude::*;
+
+/// Creates an egg with its top-left corner at `y` and centered horizontally on `x`.
+/// Eggs are made of a transform, a sprite, a collider and a pickup.
+pub fn spawn_egg(world: &mut World, x: f32, y: f32) -> Entity {
+    let entity = world.spawn();
+    world.place(
+        entity,
+        Transform::new(Rect {
+            x: x - EGG_SIZE.x / 2.0, // Center the egg horizontally
+            y,
+            w: EGG_SIZE.x,
+            h: EGG_SIZE.y,
+        }),
+    );
+    world
+        .sprites
+        .insert(entity, Sprite::new(SpriteImage::Egg, Layer::Items));
+    world.colliders.insert(entity, Collider::DEFAULT);
+    world.pickups.insert(
+        entity,
+        Pickup {
+            eggs: 1,
+            points: EGG_POINTS,
+        },
+    );
+    entity
+}
+
+/// Creates a spike centered horizontally on `x`, resting on the ground at `surface_y`.
+/// Spikes are made of a transform, a sprite, a collider and a sharp hazard.
+pub fn spawn_spike(world: &mut World, x: f32, surface_y: f32) -> Entity {
+    let entity = world.spawn();
+    world.place(
+        entity,
+        Transform::new(Rect {
+            x: x - SPIKE_SIZE.x / 2.0, // Center horizontally on the spot
+            // Position spike just above the ground surface
+            y: surface_y - SPIKE_SIZE.y + 5.0,
+            w: SPIKE_SIZE.x,
+            h: SPIKE_SIZE.y,
+        }),
+    );
+    world
+        .sprites
+        .insert(entity, Sprite::new(SpriteImage::Spike, Layer::Hazards));
+    world.colliders.insert(entity, Collider::DEFAULT);
+    world.hazards.insert(entity, Hazard::Sharp);
+    entity
+}
+
+/// Creates the house (the end goal) centered horizontally on `x`, with its bottom at `bottom`.
+/// The house is made of a transform, a sprite and a collider; the level remembers which
+/// entity it is, since reaching it ends the level.
+pub fn spawn_house(world: &mut World, x: f32, bottom: f32) -> Entity {
+    let entity = world.spawn();
+    world.place(
+        entity,
+        Transform::new(Rect {
+            x: x - HOUSE_SIZE.x / 2.0,
+            y: bottom - HOUSE_SIZE.y,
+            w: HOUSE_SIZE.x,
+            h: HOUSE_SIZE.y,
+        }),
+    );
+    world
+        .sprites
+        .insert(entity, Sprite::new(SpriteImage::House, Layer::Buildings));
+    world.colliders.insert(entity, Collider::DEFAULT);
+    entity
+}
@@ -0,0 +1,24 @@
+//! Everything that lives in a level: how each kind of thing is put together from components,
+//! and the behaviours that drive platforms, chickens and players.
+
+pub mod chicken;
+pub mod items;
+pub mod platform;
+pub mod player;
+
+use macroquad::rand::gen_range;
+
+/// Picks a random index into `weights`, where each weight is the relative chance of its index
+/// being picked. Chicken behaviours and platform kinds are chosen this way from the
+/// difficulty's weights. If every weight is zero, the first index is picked.
+pub fn weighted_index(weights: &[u32]) -> usize {
+    // Roll a number below the total weight and find which index it falls into.
+    let total: u32 = weights.iter().sum();
+    let mut roll = gen_range(0, total.max(1));
+    let mut index = 0;
+    while index < weights.len() - 1 && roll >= weights[index] {
+        roll -= weights[index];
+        index += 1;
+    }
+    index
+}
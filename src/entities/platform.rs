@@ -0,0 +1,315 @@
+//! Floating platforms, and the different ways they behave.
+
+use crate::constants::*;
+use crate::entities::weighted_index;
+use crate::world::components::{Behaviour, Layer, Sprite, SpriteImage, Transform};
+use crate::world::{Entity, World};
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+/// The state of a crumbling platform.
+pub enum CrumbleState {
+    /// Solid and waiting for the player to step on it.
+    Solid,
+    /// Stood on and about to fall. Holds the seconds left before it gives way.
+    Shaking(f32),
+    /// Falling out of the level. Holds the seconds left before it reappears.
+    Falling(f32),
+}
+
+/// The different kinds of platforms, each with its own behaviour.
+pub enum PlatformKind {
+    /// Never moves. Used for the ground and most floating platforms.
+    Static,
+    /// Glides back and forth along `direction`, carrying the player with it.
+    Moving {
+        /// Unit vector of the direction the platform travels in (horizontal or vertical).
+        direction: Vec2,
+        /// Distance currently travelled away from the starting point.
+        travelled: f32,
+        /// `1.0` while moving away from the start, `-1.0` while moving back.
+        heading: f32,
+    },
+    /// Falls shortly after the player stands on it, then comes back later.
+    Crumbling(CrumbleState),
+    /// Launches the player high into the air when they land on it.
+    Bouncy,
+    /// Appears and disappears on a fixed cycle. Holds the time into the current cycle.
+    Timed(f32),
+}
+
+impl PlatformKind {
+    /// Picks a random kind for a floating platform,
+    /// using `weights` (static, moving, crumbling, bouncy, timed) as relative chances.
+    pub fn choose(weights: [u32; 5]) -> PlatformKind {
+        match weighted_index(&weights) {
+            0 => PlatformKind::Static,
+            1 => PlatformKind::Moving {
+                // Half of the moving platforms go sideways, the other half up and down.
+                direction: if gen_range(0, 2) == 0 {
+                    Vec2::X
+                } else {
+                    Vec2::Y
+                },
+                travelled: 0.0,
+                heading: 1.0,
+            },
+            2 => PlatformKind::Crumbling(CrumbleState::Solid),
+            3 => PlatformKind::Bouncy,
+            // Start timed platforms at different points of their cycle so they don't blink in sync.
+            _ => PlatformKind::Timed(gen_range(0.0, TIMED_PLATFORM_PERIOD)),
+        }
+    }
+
+    /// The size of a floating platform of this kind.
+    pub fn size(&self) -> Vec2 {
+        match self {
+            PlatformKind::Static => PLATFORM_BAR_SIZE,
+            PlatformKind::Moving { .. } => CHOCO_BAR_SIZE,
+            PlatformKind::Crumbling(_) => {
+                Vec2::new(PLATFORM_TILE_SIZE.x * 4.0, PLATFORM_TILE_SIZE.y)
+            }
+            PlatformKind::Bouncy => Vec2::new(COOKIE_TILE_SIZE.x * 3.0, COOKIE_TILE_SIZE.y),
+            PlatformKind::Timed(_) => Vec2::new(CHOCO_TILE_SIZE.x * 4.0, CHOCO_TILE_SIZE.y),
+        }
+    }
+
+    /// The sprite a platform of this kind is drawn with.
+    pub fn sprite(&self) -> Sprite {
+        match self {
+            PlatformKind::Static => Sprite::new(SpriteImage::Platform, Layer::Platforms),
+            PlatformKind::Moving { .. } => Sprite::new(SpriteImage::ChocoBar, Layer::Platforms),
+            PlatformKind::Crumbling(_) => Sprite::tiled(
+                SpriteImage::PlatformTile,
+                Layer::Platforms,
+                PLATFORM_TILE_SIZE,
+            ),
+            PlatformKind::Bouncy => {
+                Sprite::tiled(SpriteImage::CookieTile, Layer::Platforms, COOKIE_TILE_SIZE)
+            }
+            PlatformKind::Timed(_) => {
+                Sprite::tiled(SpriteImage::ChocoTile, Layer::Platforms, CHOCO_TILE_SIZE)
+            }
+        }
+    }
+}
+
+/// The behaviour of a platform the player can stand on. Its position and size are in its transform.
+pub struct Platform {
+    /// What kind of platform this is, along with any state that kind needs.
+    pub kind: PlatformKind,
+    /// Where the platform started. Moving platforms travel around it
+    /// and crumbling platforms return to it.
+    pub origin: Vec2,
+    /// How far the platform moved during the last update. Used to carry the player along.
+    pub last_move: Vec2,
+}
+
+impl Platform {
+    /// Returns `true` if the player can currently stand on this platform.
+    pub fn is_solid(&self) -> bool {
+        match &self.kind {
+            PlatformKind::Crumbling(CrumbleState::Falling(_)) => false,
+            PlatformKind::Timed(time) => *time < TIMED_PLATFORM_VISIBLE_TIME,
+            _ => true,
+        }
+    }
+
+    /// Advances the platform's behaviour by one frame.
+    /// `rect`: The platform's position and size, moved by moving and falling platforms.
+    /// `player_on_top`: Whether the player is standing on this platform.
+    /// `delta_time`: The time in seconds since the last frame update.
+    pub fn update(&mut self, rect: &mut Rect, player_on_top: bool, delta_time: f32) {
+        let start = rect.point();
+
+        match &mut self.kind {
+            PlatformKind::Static | PlatformKind::Bouncy => {}
+            PlatformKind::Moving {
+                direction,
+                travelled,
+                heading,
+            } => {
+                // Move along the direction, turning around at either end of the range.
+                // Going past an end bounces back by the same distance, so where the platform
+                // is depends only on the total time, not on how it was split into frames
+                // (a race server and its clients step by different amounts).
+                *travelled += *heading * MOVING_PLATFORM_SPEED * delta_time;
+                while travelled.abs() > MOVING_PLATFORM_RANGE {
+                    *travelled = travelled.signum() * 2.0 * MOVING_PLATFORM_RANGE - *travelled;
+                    *heading = -*heading;
+                }
+                let position = self.origin + *direction * *travelled;
+                rect.move_to(position);
+            }
+            PlatformKind::Crumbling(state) => match state {
+                CrumbleState::Solid => {
+                    if player_on_top {
+                        *state = CrumbleState::Shaking(CRUMBLE_DELAY);
+                    }
+                }
+                CrumbleState::Shaking(time_left) => {
+                    *time_left -= delta_time;
+                    if *time_left <= 0.0 {
+                        *state = CrumbleState::Falling(CRUMBLE_RESPAWN_TIME);
+                    }
+                }
+                CrumbleState::Falling(time_left) => {
+                    *time_left -= delta_time;
+                    // Drop at a steady speed while out of play.
+                    rect.y += GRAVITY * 0.5 * delta_time;
+                    if *time_left <= 0.0 {
+                        // Reappear where the platform started.
+                        *state = CrumbleState::Solid;
+                        rect.move_to(self.origin);
+                    }
+                }
+            },
+            PlatformKind::Timed(time) => {
+                // `%` wraps the timer back to zero at the end of each cycle.
+                *time = (*time + delta_time) % TIMED_PLATFORM_PERIOD;
+            }
+        }
+
+        // Remember how far we moved so the player standing on us can move too.
+        self.last_move = rect.point() - start;
+    }
+
+    /// Updates the platform's sprite to show its state.
+    pub fn update_sprite(&self, sprite: &mut Sprite) {
+        match &self.kind {
+            // Shake sideways just before falling, as a warning.
+            PlatformKind::Crumbling(CrumbleState::Shaking(_)) => {
+                sprite.offset.x = gen_range(-2.0, 2.0);
+            }
+            PlatformKind::Crumbling(_) => sprite.offset.x = 0.0,
+            PlatformKind::Timed(time) => {
+                // Fade out during the last half second before disappearing,
+                // and show a faint outline while the platform is gone.
+                sprite.tint.a = if *time < TIMED_PLATFORM_VISIBLE_TIME {
+                    ((TIMED_PLATFORM_VISIBLE_TIME - *time) * 2.0).min(1.0)
+                } else {
+                    0.15
+                };
+            }
+            PlatformKind::Static | PlatformKind::Moving { .. } | PlatformKind::Bouncy => {}
+        }
+    }
+}
+
+/// Creates a platform of the given kind with its top-left corner at `position`.
+/// Platforms are made of a transform, a sprite and a platform behaviour.
+pub fn spawn_platform(world: &mut World, position: Vec2, size: Vec2, kind: PlatformKind) -> Entity {
+    let entity = world.spawn();
+    world.place(
+        entity,
+        Transform::new(Rect::new(position.x, position.y, size.x, size.y)),
+    );
+    let mut sprite = kind.sprite();
+    let platform = Platform {
+        kind,
+        origin: position,
+        last_move: Vec2::ZERO,
+    };
+    platform.update_sprite(&mut sprite);
+    world.sprites.insert(entity, sprite);
+    world
+        .behaviours
+        .insert(entity, Behaviour::Platform(platform));
+    entity
+}
+
+/// Every platform in `world`: its entity, where it is, and its behaviour.
+pub fn platforms(world: &World) -> impl Iterator<Item = (Entity, Rect, &Platform)> {
+    world
+        .behaviours
+        .iter()
+        .filter_map(|(entity, behaviour)| match behaviour {
+            Behaviour::Platform(platform) => {
+                let rect = world.transforms.get(entity)?.rect;
+                Some((entity, rect, platform))
+            }
+            _ => None,
+        })
+}
+
+/// The platforms in `world` that might overlap `area`, found through the grid instead of
+/// looking at every platform: their entities, where they are, and their behaviours.
+pub fn platforms_in(world: &World, area: Rect) -> impl Iterator<Item = (Entity, Rect, &Platform)> {
+    world
+        .query(area)
+        .into_iter()
+        .filter_map(|entity| match world.behaviours.get(entity)? {
+            Behaviour::Platform(platform) => {
+                let rect = world.transforms.get(entity)?.rect;
+                Some((entity, rect, platform))
+            }
+            _ => None,
+        })
+}
+
+/// Moves the platforms and advances their timers, and updates their sprites to match.
+/// `standing_on`: The platforms players are standing on, which crumbling platforms react to.
+/// `delta_time`: The time in seconds since the last frame update.
+pub fn platform_system(world: &mut World, standing_on: &[Entity], delta_time: f32) {
+    for (entity, behaviour) in world.behaviours.iter_mut() {
+        let Behaviour::Platform(platform) = behaviour else {
+            continue;
+        };
+        if let Some(transform) = world.transforms.get_mut(entity) {
+            platform.update(
+                &mut transform.rect,
+                standing_on.contains(&entity),
+                delta_time,
+            );
+            world.grid.update(entity, transform.rect);
+        }
+        if let Some(sprite) = world.sprites.get_mut(entity) {
+            platform.update_sprite(sprite);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A moving or timed platform after `seconds`, stepped `steps` frames at a time.
+    fn after(kind: PlatformKind, seconds: f32, steps: u32) -> (Rect, bool) {
+        let mut platform = Platform {
+            kind,
+            origin: Vec2::new(100.0, 300.0),
+            last_move: Vec2::ZERO,
+        };
+        let mut rect = Rect::new(100.0, 300.0, CHOCO_BAR_SIZE.x, CHOCO_BAR_SIZE.y);
+        for _ in 0..steps {
+            platform.update(&mut rect, false, seconds / steps as f32);
+        }
+        (rect, platform.is_solid())
+    }
+
+    #[test]
+    fn platforms_end_up_in_the_same_place_however_time_is_split() {
+        // Long enough for moving platforms to turn around several times.
+        let seconds = 37.3;
+        let moving = || PlatformKind::Moving {
+            direction: Vec2::X,
+            travelled: 0.0,
+            heading: 1.0,
+        };
+        let (at_server_rate, _) = after(moving(), seconds, 30 * 37);
+        for steps in [1, 7, 60 * 37, 144 * 37] {
+            let (rect, _) = after(moving(), seconds, steps);
+            assert!(
+                (rect.x - at_server_rate.x).abs() < 0.1,
+                "{steps} steps: {} instead of {}",
+                rect.x,
+                at_server_rate.x
+            );
+        }
+
+        let (_, solid) = after(PlatformKind::Timed(0.0), seconds, 30 * 37);
+        for steps in [1, 7, 60 * 37] {
+            assert_eq!(after(PlatformKind::Timed(0.0), seconds, steps).1, solid);
+        }
+    }
+}
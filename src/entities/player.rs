@@ -0,0 +1,504 @@
+//! The player characters, their controls and their physics.
+
+use crate::assets::Assets;
+use crate::audio::play_effect;
+use crate::captions::{Captions, Cue};
+use crate::constants::*;
+use crate::entities::platform::{platforms_in, PlatformKind};
+use crate::level::Level;
+use crate::locale::{fill, Strings};
+use crate::particles::{Particles, DUST_PUFF, EGG_SPARKLE, FEATHERS};
+use crate::physics::{GameEntity, MovingGameEntity};
+use crate::text::{draw_text_line, HUD_TEXT};
+use crate::world::components::{Behaviour, Hazard};
+use crate::world::Entity;
+use macroquad::prelude::*;
+
+/// Represents the direction the player is currently facing. Used for drawing the correct sprite.
+pub enum MoveDirection {
+    Left,
+    Right,
+}
+
+/// The keys one player uses to move and jump.
+#[derive(Clone, Copy)]
+pub struct Controls {
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub jump: KeyCode,
+}
+
+impl Controls {
+    /// The arrow keys, used in single-player games and by player 2 in two-player games.
+    pub const ARROWS: Controls = Controls {
+        left: KeyCode::Left,
+        right: KeyCode::Right,
+        jump: KeyCode::Up,
+    };
+    /// A, D and W, used by player 1 in two-player games.
+    pub const WASD: Controls = Controls {
+        left: KeyCode::A,
+        right: KeyCode::D,
+        jump: KeyCode::W,
+    };
+}
+
+/// The buttons a player is holding during one frame.
+/// Kept separate from the keyboard so it can be sent to a race server and replayed.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct PlayerInput {
+    pub left: bool,
+    pub right: bool,
+    pub jump: bool,
+}
+
+impl PlayerInput {
+    /// Reads the keys in `controls`. Left and right are held, jump is only pressed *this frame*.
+    pub fn read(controls: Controls) -> PlayerInput {
+        PlayerInput {
+            left: is_key_down(controls.left),
+            right: is_key_down(controls.right),
+            jump: is_key_pressed(controls.jump),
+        }
+    }
+}
+
+/// One player character, with their own controls, score and lives.
+pub struct Player {
+    /// The player's position, size and velocity.
+    pub body: MovingGameEntity,
+    /// The direction the player is facing.
+    pub direction: MoveDirection,
+    /// The keys this player uses.
+    pub controls: Controls,
+    /// Tint color, so players can tell their characters apart.
+    pub tint: Color,
+    /// Points scored by this player.
+    pub score: u32,
+    /// The number of eggs this player is carrying.
+    pub eggs_collected: u32,
+    /// Lives left. A player with no lives left is out of the game.
+    pub lives: u32,
+    /// How long (in seconds) the player can't be hurt for after losing a life.
+    pub invulnerable_time: f32,
+    /// In endless mode, points earned so far for the distance travelled.
+    pub distance_points: u32,
+    /// The platform the player is standing on, if any.
+    /// Used to carry the player along with moving platforms.
+    pub standing_on: Option<Entity>,
+}
+
+impl Player {
+    /// Creates a player at the start of the level.
+    /// `controls`: The keys this player uses.
+    /// `tint`: Tint color for the player's sprite.
+    /// `lives`: How many lives the player starts with.
+    /// `offset`: How far right of the start position the player begins, so players don't overlap.
+    pub fn new(controls: Controls, tint: Color, lives: u32, offset: f32) -> Player {
+        Player {
+            body: MovingGameEntity {
+                entity: GameEntity {
+                    rect: Rect {
+                        // Center the player horizontally at the start position
+                        x: PLAYER_START_POS.x + offset - PLAYER_SIZE.x / 2.0,
+                        // Center the player vertically at the start position
+                        y: PLAYER_START_POS.y - PLAYER_SIZE.y / 2.0,
+                        w: PLAYER_SIZE.x, // Use predefined player width
+                        h: PLAYER_SIZE.y, // Use predefined player height
+                    },
+                },
+                velocity: Vec2::ZERO, // Start with no initial movement
+            },
+            direction: MoveDirection::Right, // Start facing right
+            controls,
+            tint,
+            score: 0,
+            eggs_collected: 0,
+            lives,
+            invulnerable_time: 0.0,
+            distance_points: 0,
+            standing_on: None,
+        }
+    }
+
+    /// Whether the player has run out of lives.
+    pub fn is_out(&self) -> bool {
+        self.lives == 0
+    }
+
+    /// Puts the player back into the game at `center`, standing still and briefly invulnerable.
+    pub fn respawn(&mut self, center: Vec2) {
+        self.body.entity.rect.x = center.x - PLAYER_SIZE.x / 2.0;
+        self.body.entity.rect.y = center.y - PLAYER_SIZE.y / 2.0;
+        self.body.velocity = Vec2::ZERO;
+        self.standing_on = None;
+        self.invulnerable_time = RESPAWN_INVULNERABILITY;
+    }
+
+    /// Reads this player's keys and sets their velocity and facing direction.
+    /// `particles`: Where the dust kicked up by jumping goes.
+    /// `captions`: Where the jump sound is captioned.
+    pub fn handle_input(
+        &mut self,
+        assets: &Assets,
+        particles: &mut Particles,
+        captions: &mut Captions,
+    ) {
+        if self.apply_input(PlayerInput::read(self.controls)) {
+            play_effect(&assets.jump); // Play jump sound effect
+            captions.show(Cue::Jump);
+            self.kick_up_dust(particles);
+        }
+    }
+
+    /// Throws a puff of dust out from under the player's feet.
+    pub fn kick_up_dust(&self, particles: &mut Particles) {
+        let rect = self.body.entity.rect;
+        particles.burst(&DUST_PUFF, Vec2::new(rect.center().x, rect.bottom()));
+    }
+
+    /// Sets the player's velocity and facing direction from the buttons held.
+    /// Returns `true` if the player jumped.
+    pub fn apply_input(&mut self, input: PlayerInput) -> bool {
+        // Check left/right movement buttons.
+        match (input.left, input.right) {
+            (true, false) => {
+                // Left key is down, Right key is up
+                self.direction = MoveDirection::Left; // Set facing direction
+                self.body.velocity.x = -PLAYER_MOVEMENT_SPEED; // Set horizontal velocity leftwards
+            }
+            (false, true) => {
+                // Left key is up, Right key is down
+                self.direction = MoveDirection::Right; // Set facing direction
+                self.body.velocity.x = PLAYER_MOVEMENT_SPEED; // Set horizontal velocity rightwards
+            }
+            _ => {
+                // Neither or both keys are pressed
+                self.body.velocity.x = 0.0; // Stop horizontal movement
+            }
+        };
+        // Check jump button, which is only set on the frame it was pressed.
+        // `velocity.y == 0.0` checks if the player is on the ground (or apex of jump).
+        let jumped = input.jump && self.body.velocity.y == 0.0;
+        if jumped {
+            self.body.velocity.y = -PLAYER_JUMP_SPEED; // Set vertical velocity upwards (jump)
+        }
+        jumped
+    }
+
+    /// Moves the player for one frame: riding platforms, gravity, landing and tile collisions.
+    /// `level`: The level the player is moving through.
+    /// `assets`: Used to play the bounce sound.
+    /// `particles`: Where the dust kicked up by landing goes.
+    /// `captions`: Where the bounce sound is captioned.
+    /// `delta_time`: The time in seconds since the last frame.
+    pub fn update(
+        &mut self,
+        level: &Level,
+        assets: &Assets,
+        particles: &mut Particles,
+        captions: &mut Captions,
+        delta_time: f32,
+    ) {
+        let falling_fast = self.body.velocity.y > LANDING_DUST_SPEED;
+        if self.step(level, delta_time) {
+            play_effect(&assets.jump);
+            captions.show(Cue::Jump);
+        }
+        // Landing stops the fall, so a hard landing is a fast fall that just stopped.
+        if falling_fast && self.body.velocity.y == 0.0 {
+            self.kick_up_dust(particles);
+        }
+    }
+
+    /// The physics behind `update`, without any sound, so a headless race server can run it too.
+    /// Returns `true` if the player bounced off a bouncy platform.
+    pub fn step(&mut self, level: &Level, delta_time: f32) -> bool {
+        self.invulnerable_time = (self.invulnerable_time - delta_time).max(0.0);
+        let player = &mut self.body;
+
+        // Carry the player along with the platform they are standing on.
+        let standing_on = self
+            .standing_on
+            .and_then(|entity| level.world.behaviours.get(entity));
+        if let Some(Behaviour::Platform(platform)) = standing_on {
+            player.entity.rect.x += platform.last_move.x;
+            player.entity.rect.y += platform.last_move.y;
+        }
+
+        // Apply gravity to the player's vertical velocity.
+        player.velocity.y += GRAVITY * delta_time;
+
+        // --- Platform Collision Detection (Ground Check) ---
+        // Only platforms whose top is just under the player's feet can be landed on,
+        // so ask the grid for the platforms in that strip.
+        let feet = Rect::new(
+            player.entity.rect.x,
+            player.entity.rect.bottom() - GROUND_DETECTION_BUFFER,
+            player.entity.rect.w,
+            GROUND_DETECTION_BUFFER + (player.velocity.y * delta_time).max(0.0),
+        );
+        // Find the first platform the player might land on.
+        let ground_collision =
+            platforms_in(&level.world, feet).find_map(|(entity, platform_rect, platform)| {
+                // Platforms that have fallen or faded away can't be landed on.
+                if !platform.is_solid() {
+                    return None;
+                }
+
+                // Check if player's horizontal range overlaps with the platform's horizontal range.
+                let horizontally_overlapping = player.entity.rect.right() > platform_rect.x
+                    && player.entity.rect.x < platform_rect.right();
+
+                // Check if player is moving downwards or is stationary vertically.
+                let falling_towards_platform = player.velocity.y >= 0.0;
+                // Check if the player's bottom is slightly above or at the platform's top.
+                let close_to_platform_top =
+                    player.entity.rect.bottom() <= platform_rect.y + GROUND_DETECTION_BUFFER;
+                // Predict if the player *will* be below the platform top in the next frame.
+                let will_intersect_next_frame =
+                    player.entity.rect.bottom() + player.velocity.y * delta_time >= platform_rect.y;
+
+                // If all conditions are met, the player is about to land on this platform.
+                if horizontally_overlapping
+                    && falling_towards_platform
+                    && close_to_platform_top
+                    && will_intersect_next_frame
+                {
+                    // Return which platform it is, the Y-coordinate of its top surface,
+                    // and whether it is bouncy.
+                    let bouncy = matches!(platform.kind, PlatformKind::Bouncy);
+                    Some((entity, platform_rect.y, bouncy))
+                } else {
+                    // Otherwise, no collision with this platform.
+                    None
+                }
+            });
+
+        // Update player position based on velocity, stopping at walls, floors and ceilings.
+        level.tile_map.move_and_collide(player, delta_time);
+
+        // --- Handle Ground Collision Response ---
+        // If `ground_collision` found a platform (`Some((entity, platform_top, bouncy))`)...
+        self.standing_on = None;
+        let mut bounced = false;
+        if let Some((entity, platform_top, bouncy)) = ground_collision {
+            // Snap the player's bottom edge to the top of the platform.
+            player.entity.rect.y = platform_top - player.entity.rect.h;
+            if bouncy {
+                // Bouncy platforms launch the player instead of stopping them.
+                player.velocity.y = -BOUNCY_PLATFORM_SPEED;
+                bounced = true;
+            } else {
+                // Stop vertical movement.
+                player.velocity.y = 0.0;
+                self.standing_on = Some(entity);
+            }
+        }
+        bounced
+    }
+
+    /// Whether the player has fallen too far below the bottom of the level.
+    pub fn fell_out(&self, level: &Level) -> bool {
+        self.body.entity.rect.bottom() > level.tile_map.bottom() + 100.0
+    }
+
+    /// Whether the player is touching a spike, or anything else sharp
+    /// (and isn't protected after losing a life).
+    pub fn touches_spike(&self, level: &Level) -> bool {
+        let bounds = self.body.entity.get_collision_bounds();
+        let world = &level.world;
+        self.invulnerable_time <= 0.0
+            && world
+                .query(bounds)
+                .into_iter()
+                .filter(|entity| matches!(world.hazards.get(*entity), Some(Hazard::Sharp)))
+                .filter_map(|entity| world.collision_bounds(entity))
+                .any(|spike| bounds.overlaps(&spike))
+    }
+
+    /// Whether the player is touching the level's house.
+    pub fn reached_house(&self, level: &Level) -> bool {
+        level
+            .house
+            .and_then(|house| level.world.collision_bounds(house))
+            .is_some_and(|house| self.body.entity.get_collision_bounds().overlaps(&house))
+    }
+
+    /// Checks the player against eggs, chickens, spikes and the bottom of the level,
+    /// collecting eggs and stomping chickens along the way.
+    /// Returns `true` if the player lost a life this frame.
+    /// `particles`: Where the sparkles of collected eggs and the feathers of stomped chickens go.
+    /// `captions`: Where the sounds of hits and pickups are captioned.
+    pub fn check_collisions(
+        &mut self,
+        level: &mut Level,
+        assets: &Assets,
+        particles: &mut Particles,
+        captions: &mut Captions,
+    ) -> bool {
+        // --- Check Player Falling Off Screen ---
+        // If player falls too far below the level, they die (even while invulnerable).
+        // `player_died` is set when the player loses a life this frame.
+        let mut player_died = self.fell_out(level);
+
+        // --- Spike Collision ---
+        // Check if the player collides with any spike.
+        if self.touches_spike(level) {
+            play_effect(&assets.spike_hit); // Play hit sound
+            captions.show(Cue::SpikeHit);
+            player_died = true;
+        }
+
+        let player = &mut self.body;
+        let bounds = player.entity.get_collision_bounds();
+        let world = &mut level.world;
+        // Only the entities the grid has near the player can be touching them.
+        let nearby = world.query(bounds);
+
+        // --- Pickups ---
+        // Collect everything the player is touching, like eggs.
+        let collected: Vec<Entity> = nearby
+            .iter()
+            .copied()
+            .filter(|entity| {
+                // Check if the player's collision bounds overlap with the pickup's bounds.
+                world.pickups.get(*entity).is_some()
+                    && world
+                        .collision_bounds(*entity)
+                        .is_some_and(|pickup| bounds.overlaps(&pickup))
+            })
+            .collect();
+        for entity in collected {
+            if let Some(pickup) = world.pickups.get(entity) {
+                self.eggs_collected += pickup.eggs; // Carry the eggs
+                self.score += pickup.points; // Increase score
+                play_effect(&assets.egg_collect); // Play collection sound
+                captions.show(Cue::EggCollect);
+                if let Some(transform) = world.transforms.get(entity) {
+                    particles.burst(&EGG_SPARKLE, transform.rect.center());
+                }
+            }
+            // Collected pickups disappear from the level.
+            world.despawn(entity);
+        }
+
+        // --- Stompable Hazards ---
+        // Landing on a chicken from above (stomping) hurts it; any other contact hurts the player.
+        let mut player_hurt = false;
+        for entity in nearby {
+            let Some(hazard) = world.hazards.get_mut(entity) else {
+                continue;
+            };
+            // Spikes were handled above, and defeated chickens are harmless while they fall away.
+            let Hazard::Stompable { max_health, .. } = *hazard else {
+                continue;
+            };
+            let (Some(transform), Some(collider)) =
+                (world.transforms.get(entity), world.colliders.get(entity))
+            else {
+                continue;
+            };
+            if hazard.is_defeated() || !bounds.overlaps(&collider.bounds(transform.rect)) {
+                continue;
+            }
+
+            // It's a stomp if the player is falling and their feet are in the chicken's upper half.
+            let stomping =
+                player.velocity.y > 0.0 && player.entity.rect.bottom() < transform.rect.center().y;
+            if stomping {
+                // Bounce off the chicken's head.
+                player.velocity.y = -STOMP_BOUNCE_SPEED;
+                // A chicken still recovering from the last stomp takes no damage,
+                // so there is nothing to hear or see either.
+                if hazard.is_recovering() {
+                    continue;
+                }
+                if hazard.take_hit() {
+                    // Tougher chickens are worth more points.
+                    self.score += CHICKEN_POINTS * max_health;
+                    // Knock the chicken upwards a little before it tumbles down.
+                    if let Some(velocity) = world.velocities.get_mut(entity) {
+                        *velocity = Vec2::new(velocity.x * 0.3, -250.0);
+                    }
+                }
+                play_effect(&assets.chicken_hit);
+                captions.show(Cue::ChickenHit);
+                particles.burst(&FEATHERS, transform.rect.center());
+            } else if !hazard.is_recovering() && self.invulnerable_time <= 0.0 {
+                // Side or bottom contact with a chicken that isn't recovering from a stomp.
+                player_hurt = true;
+            }
+        }
+        if player_hurt {
+            play_effect(&assets.chicken_hit); // Play hit sound
+            captions.show(Cue::ChickenHit);
+            player_died = true;
+        }
+
+        player_died
+    }
+
+    /// Draws the player using the sprite corresponding to their facing direction.
+    /// While invulnerable after losing a life, the player blinks.
+    pub fn draw(&self, assets: &Assets) {
+        let blink_hidden =
+            self.invulnerable_time > 0.0 && ((self.invulnerable_time * 10.0) as i32) % 2 == 0;
+        if blink_hidden {
+            return;
+        }
+        let texture = match self.direction {
+            MoveDirection::Right => &assets.player_right,
+            MoveDirection::Left => &assets.player_left,
+        };
+        let rect = self.body.entity.rect;
+        texture.draw(
+            rect.x,
+            rect.y,
+            self.tint,
+            DrawTextureParams {
+                dest_size: Some(rect.size()),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Draws this player's score panel at the top of the screen.
+    /// `strings`: The words to show, in the chosen language.
+    /// `x`: The panel's left edge, in virtual screen pixels.
+    /// `title`: The first line, naming the player and their score.
+    /// `eggs_text`: The second line, showing the eggs collected.
+    pub fn draw_hud(
+        &self,
+        assets: &Assets,
+        strings: &Strings,
+        x: f32,
+        title: &str,
+        eggs_text: &str,
+    ) {
+        // Draw the score panel background image, 256 by 100 pixels, a little below the top.
+        assets.score_panel.draw(
+            x,     // X position
+            23.0,  // Y position
+            WHITE, // No tint
+            DrawTextureParams {
+                dest_size: Some(Vec2::new(256.0, 100.0)),
+                ..Default::default()
+            },
+        );
+        // The lines of text inside the panel, one under the other.
+        let lives_text = if self.is_out() {
+            strings.out.to_string()
+        } else {
+            fill(strings.lives, &[&self.lives])
+        };
+        let lines = [
+            title,       // The main score text (e.g., "Score: 12")
+            eggs_text,   // The eggs line (e.g., "{egg} + 3/5")
+            &lives_text, // The number of lives left (e.g., "Lives: 3"), or that the player is out
+        ];
+        for (index, line) in lines.iter().enumerate() {
+            draw_text_line(assets, line, x + 50.0, 54.0 + 23.0 * index as f32, HUD_TEXT);
+        }
+    }
+}
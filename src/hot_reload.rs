@@ -0,0 +1,169 @@
+//! Reloading images and sounds while the game runs, so artists see their changes straight away
+//! instead of rebuilding the game. Only built with the `hot-reload` feature:
+//! `cargo run --features hot-reload`.
+//!
+//! Every asset is watched in the file it comes from: the asset pack replacing it, if any,
+//! or otherwise its default file in this project's `assets` directory. Pack manifests are
+//! watched too, so adding or removing a line takes effect immediately.
+//! (Levels are generated from seeds rather than loaded from files, so there are none to watch.)
+//! The font is only loaded at startup; restart the game to see a new one.
+
+use crate::assets::{load_png_texture_from_bytes, load_sound, AssetPack, Assets, SOUNDS, TEXTURES};
+use crate::atlas::TextureRegion;
+use crate::audio::play_music;
+use crate::constants::*;
+use macroquad::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// When `path` was last changed, or `None` if it can't be read.
+fn last_modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// One asset, and the file it is loaded from.
+struct WatchedFile {
+    /// The asset's name, e.g. "chicken".
+    name: &'static str,
+    /// Whether the asset is a sound (otherwise it's an image).
+    is_sound: bool,
+    /// The file the asset comes from.
+    path: PathBuf,
+    /// When the file was last changed, as of the last reload.
+    modified: Option<SystemTime>,
+}
+
+/// Watches the asset files for changes, and reloads the ones that changed.
+pub struct HotReloader {
+    /// The asset packs in use, in order. Later packs win.
+    pack_directories: Vec<PathBuf>,
+    /// Every pack's manifest, and when it was last changed.
+    manifests: Vec<(PathBuf, Option<SystemTime>)>,
+    /// Every asset, and the file it comes from.
+    watched: Vec<WatchedFile>,
+    /// When (in seconds since the game started) to look at the files again.
+    next_check: f64,
+}
+
+impl HotReloader {
+    /// Starts watching the files of the assets that were just loaded.
+    /// `pack_directories`: The asset packs in use, in order. Later packs win.
+    pub fn new(pack_directories: &[PathBuf]) -> HotReloader {
+        let mut reloader = HotReloader {
+            pack_directories: pack_directories.to_vec(),
+            manifests: Vec::new(),
+            watched: Vec::new(),
+            next_check: 0.0,
+        };
+        reloader.watch_files();
+        reloader
+    }
+
+    /// Works out which file each asset comes from, by reading the pack manifests again.
+    /// Assets whose file changed are reloaded on the next check.
+    fn watch_files(&mut self) {
+        let packs: Vec<AssetPack> = self
+            .pack_directories
+            .iter()
+            .filter_map(|directory| AssetPack::load(directory).ok())
+            .collect();
+        self.manifests = self
+            .pack_directories
+            .iter()
+            .map(|directory| {
+                let manifest = directory.join(ASSET_MANIFEST_FILE);
+                let modified = last_modified(&manifest);
+                (manifest, modified)
+            })
+            .collect();
+
+        // `env!` gives the project directory at compile time, which is where the
+        // default files are while developing.
+        let assets_directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        let all_assets = TEXTURES
+            .iter()
+            .map(|asset| (asset, false))
+            .chain(SOUNDS.iter().map(|asset| (asset, true)));
+        let watched: Vec<WatchedFile> = all_assets
+            .map(|(asset, is_sound)| {
+                let path = packs
+                    .iter()
+                    .rev()
+                    .find_map(|pack| pack.file_for(asset.name))
+                    .map_or_else(|| assets_directory.join(asset.path), Path::to_owned);
+                // Keep the old time for files that are still the same, so they aren't reloaded.
+                // Files that are new to the asset are reloaded on the next check,
+                // and at startup every file is as it was just loaded.
+                let modified = match self.watched.iter().find(|file| file.name == asset.name) {
+                    Some(old) if old.path == path => old.modified,
+                    Some(_) => None,
+                    None => last_modified(&path),
+                };
+                WatchedFile {
+                    name: asset.name,
+                    is_sound,
+                    path,
+                    modified,
+                }
+            })
+            .collect();
+        self.watched = watched;
+    }
+
+    /// Reloads any asset whose file has changed since it was loaded.
+    /// Checks the files every `HOT_RELOAD_INTERVAL` seconds; call it once a frame.
+    pub async fn update(&mut self, assets: &mut Assets) {
+        if get_time() < self.next_check {
+            return;
+        }
+        self.next_check = get_time() + HOT_RELOAD_INTERVAL;
+
+        // A changed manifest can point assets at different files.
+        let manifests_changed = self
+            .manifests
+            .iter()
+            .any(|(manifest, modified)| last_modified(manifest) != *modified);
+        if manifests_changed {
+            self.watch_files();
+        }
+
+        for file in &mut self.watched {
+            let modified = last_modified(&file.path);
+            // Skip files that haven't changed, or that are missing (keeping what was loaded).
+            if modified.is_none() || modified == file.modified {
+                continue;
+            }
+            file.modified = modified;
+            let Ok(bytes) = std::fs::read(&file.path) else {
+                continue;
+            };
+
+            let result = if file.is_sound {
+                load_sound(&bytes).await.map(|sound| {
+                    if let Some(slot) = assets.sound_mut(file.name) {
+                        *slot = Some(sound);
+                    }
+                })
+            } else {
+                // The loader sets the texture to `FilterMode::Nearest` again, keeping pixel art sharp.
+                // A reloaded image gets a texture of its own, outside the atlas.
+                load_png_texture_from_bytes(&bytes).map(|texture| {
+                    if let Some(slot) = assets.texture_mut(file.name) {
+                        *slot = TextureRegion::whole(texture);
+                    }
+                })
+            };
+            match result {
+                Ok(()) => eprintln!("Reloaded {} from {}", file.name, file.path.display()),
+                // A half-saved file often fails to load; it's tried again once it changes.
+                Err(reason) => eprintln!("Could not reload {}: {reason}", file.name),
+            }
+            // Replacing the music stopped the old one, so start the new one.
+            if file.name == "background_music" {
+                play_music(&assets.background_music);
+            }
+        }
+    }
+}
@@ -0,0 +1,819 @@
+//! The level: its tile terrain, and generating levels from a seed that can be completed.
+
+use crate::assets::Assets;
+use crate::constants::*;
+use crate::entities::chicken::{spawn_chicken, Chicken, ChickenBehaviour};
+use crate::entities::items::{spawn_egg, spawn_house, spawn_spike};
+use crate::entities::platform::{platforms, spawn_platform, PlatformKind};
+use crate::options::DifficultySettings;
+use crate::parallax::{ParallaxLayer, ENDLESS_LAYERS, STORY_LAYERS};
+use crate::physics::MovingGameEntity;
+use crate::world::components::{Behaviour, Hazard};
+use crate::world::{Entity, World};
+use macroquad::prelude::*;
+use macroquad::rand::{gen_range, srand};
+
+/// A single square of terrain in the tile map.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tile {
+    /// Nothing here; the player can move through it.
+    Empty,
+    /// Solid chocolate, used for the ground beneath the surface.
+    Choco,
+    /// Solid cookie, used for the walkable top layer of the ground.
+    Cookie,
+}
+
+impl Tile {
+    /// Returns `true` if the player collides with this tile.
+    pub fn is_solid(self) -> bool {
+        self != Tile::Empty
+    }
+}
+
+/// A grid of terrain tiles. Each column holds `TILE_MAP_ROWS` tiles, from top to bottom.
+/// Because terrain is built from small tiles, it can take any shape: stairs, pits, tunnels...
+/// Columns can be added on the right and dropped on the left, so the map can stream
+/// through an endless level without growing forever.
+pub struct TileMap {
+    /// World position of the top-left corner of column 0's top tile.
+    pub origin: Vec2,
+    /// The column number of the first column still stored (earlier ones have been dropped).
+    pub first_column: i32,
+    /// The tiles, stored column by column from left to right, starting at `first_column`.
+    pub columns: Vec<[Tile; TILE_MAP_ROWS]>,
+}
+
+impl TileMap {
+    /// Creates an empty tile map whose first column starts at world position `left`.
+    /// `bottom`: The world y position of the bottom edge of the terrain.
+    pub fn new(left: f32, bottom: f32) -> TileMap {
+        TileMap {
+            origin: Vec2::new(left, bottom - TILE_MAP_ROWS as f32 * TILE_SIZE),
+            first_column: 0,
+            columns: Vec::new(),
+        }
+    }
+
+    /// The world y position of the bottom of the map.
+    pub fn bottom(&self) -> f32 {
+        self.origin.y + TILE_MAP_ROWS as f32 * TILE_SIZE
+    }
+
+    /// The world x position just past the last column.
+    pub fn end_x(&self) -> f32 {
+        self.origin.x + (self.first_column + self.columns.len() as i32) as f32 * TILE_SIZE
+    }
+
+    /// Adds terrain until the map reaches world position `end_x`:
+    /// flat ground broken up by stairs, pits and tunnels.
+    /// `flat_from`: Terrain past this x position is kept flat (for example around the house).
+    /// `pit_chance`: Extra chance (0 to 1) of each feature being a pit, used to ramp up endless levels.
+    pub fn extend(&mut self, end_x: f32, flat_from: f32, pit_chance: f32) {
+        // Build the terrain one feature at a time until the map is long enough.
+        while self.end_x() < end_x {
+            let x = self.end_x();
+            // Keep the start of the level and the flat area at the end free of obstacles.
+            let flat_only = x < SAFE_START_X || x > flat_from;
+            let feature = if flat_only {
+                0
+            } else if gen_range(0.0, 1.0) < pit_chance {
+                2
+            } else {
+                gen_range(0, 5)
+            };
+
+            let columns = &mut self.columns;
+            match feature {
+                // Stairs: climb up a few steps, walk along the top, then climb back down.
+                1 => {
+                    let steps = gen_range(1, 4);
+                    for step in 1..=steps {
+                        columns.push(Self::ground_column(GROUND_HEIGHT + step));
+                    }
+                    for _ in 0..gen_range(1, 4) {
+                        columns.push(Self::ground_column(GROUND_HEIGHT + steps));
+                    }
+                    for step in (1..steps).rev() {
+                        columns.push(Self::ground_column(GROUND_HEIGHT + step));
+                    }
+                }
+                // Pit: a short gap the player has to jump over (falling in is deadly).
+                // Always followed by some ground so two pits never join into one huge gap.
+                2 => {
+                    for _ in 0..gen_range(2, 4) {
+                        columns.push([Tile::Empty; TILE_MAP_ROWS]);
+                    }
+                    for _ in 0..2 {
+                        columns.push(Self::ground_column(GROUND_HEIGHT));
+                    }
+                }
+                // Tunnel: a solid block with a passage through it just tall enough to walk in.
+                3 => {
+                    for _ in 0..gen_range(4, 8) {
+                        let mut column = Self::ground_column(TILE_MAP_ROWS - 1);
+                        // Carve out a two-tile-high passage on top of the ground.
+                        let floor = TILE_MAP_ROWS - GROUND_HEIGHT;
+                        column[floor - 1] = Tile::Empty;
+                        column[floor - 2] = Tile::Empty;
+                        columns.push(column);
+                    }
+                }
+                // Flat ground.
+                _ => {
+                    for _ in 0..gen_range(3, 8) {
+                        columns.push(Self::ground_column(GROUND_HEIGHT));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops every column that lies completely left of world position `x`.
+    pub fn discard_before(&mut self, x: f32) {
+        let (column, _) = self.cell_at(Vec2::new(x, self.origin.y));
+        let count = (column - self.first_column).clamp(0, self.columns.len() as i32);
+        self.columns.drain(..count as usize);
+        self.first_column += count;
+    }
+
+    /// Builds a column of ground `height` tiles tall, with a cookie layer on top.
+    pub fn ground_column(height: usize) -> [Tile; TILE_MAP_ROWS] {
+        let mut column = [Tile::Empty; TILE_MAP_ROWS];
+        let top = TILE_MAP_ROWS - height.min(TILE_MAP_ROWS);
+        for (row, tile) in column.iter_mut().enumerate().skip(top) {
+            *tile = if row == top {
+                Tile::Cookie
+            } else {
+                Tile::Choco
+            };
+        }
+        column
+    }
+
+    /// Returns the tile at the given column and row, or `Tile::Empty` outside the map.
+    pub fn tile(&self, column: i32, row: i32) -> Tile {
+        let index = column - self.first_column;
+        if index < 0 || row < 0 || row >= TILE_MAP_ROWS as i32 {
+            return Tile::Empty;
+        }
+        self.columns
+            .get(index as usize)
+            .map_or(Tile::Empty, |tiles| tiles[row as usize])
+    }
+
+    /// Converts a world position into the (column, row) of the tile containing it.
+    pub fn cell_at(&self, position: Vec2) -> (i32, i32) {
+        let local = (position - self.origin) / TILE_SIZE;
+        (local.x.floor() as i32, local.y.floor() as i32)
+    }
+
+    /// Returns `true` if `rect` overlaps any solid tile.
+    pub fn overlaps_solid(&self, rect: Rect) -> bool {
+        // A tiny inset keeps rectangles that are exactly touching a tile from counting as overlapping.
+        let (first_column, first_row) = self.cell_at(rect.point());
+        let (last_column, last_row) =
+            self.cell_at(Vec2::new(rect.right() - 0.01, rect.bottom() - 0.01));
+        (first_column..=last_column)
+            .any(|column| (first_row..=last_row).any(|row| self.tile(column, row).is_solid()))
+    }
+
+    /// Returns the world y position of the highest solid tile at world position `x`,
+    /// or `None` if the column has no ground (a pit or outside the map).
+    pub fn surface_y(&self, x: f32) -> Option<f32> {
+        let (column, _) = self.cell_at(Vec2::new(x, self.origin.y));
+        (0..TILE_MAP_ROWS as i32)
+            .find(|&row| self.tile(column, row).is_solid())
+            .map(|row| self.origin.y + row as f32 * TILE_SIZE)
+    }
+
+    /// Where a player can be put back on the ground near world position `x`: the centre of a
+    /// player standing on the nearest column with ground, looking back from `x` first and then
+    /// ahead. Returns `None` if no column still stored has any ground.
+    pub fn ground_near(&self, x: f32) -> Option<Vec2> {
+        if self.columns.is_empty() {
+            return None;
+        }
+        let (start, _) = self.cell_at(Vec2::new(x, self.origin.y));
+        let last = self.first_column + self.columns.len() as i32 - 1;
+        let start = start.clamp(self.first_column, last);
+        let behind = (self.first_column..=start).rev();
+        let ahead = start + 1..=last;
+        behind.chain(ahead).find_map(|column| {
+            let center_x = self.origin.x + (column as f32 + 0.5) * TILE_SIZE;
+            let surface = self.surface_y(center_x)?;
+            Some(Vec2::new(center_x, surface - PLAYER_SIZE.y / 2.0))
+        })
+    }
+
+    /// Moves `body` by its velocity, stopping it at solid tiles.
+    /// Movement is done one axis at a time so the player can slide along walls and floors.
+    /// Returns `true` if the body landed on top of a tile.
+    pub fn move_and_collide(&self, body: &mut MovingGameEntity, delta_time: f32) -> bool {
+        // --- Horizontal movement ---
+        let rect = &mut body.entity.rect;
+        rect.x += body.velocity.x * delta_time;
+        if self.overlaps_solid(*rect) {
+            if body.velocity.x > 0.0 {
+                // Moving right: push back to the left side of the tile we ran into.
+                let (column, _) = self.cell_at(Vec2::new(rect.right(), rect.y));
+                rect.x = self.origin.x + column as f32 * TILE_SIZE - rect.w;
+            } else {
+                // Moving left: push back to the right side of the tile we ran into.
+                let (column, _) = self.cell_at(rect.point());
+                rect.x = self.origin.x + (column + 1) as f32 * TILE_SIZE;
+            }
+            body.velocity.x = 0.0;
+        }
+
+        // --- Vertical movement ---
+        rect.y += body.velocity.y * delta_time;
+        let mut landed = false;
+        if self.overlaps_solid(*rect) {
+            if body.velocity.y > 0.0 {
+                // Falling: stand on top of the tile below.
+                let (_, row) = self.cell_at(Vec2::new(rect.x, rect.bottom()));
+                rect.y = self.origin.y + row as f32 * TILE_SIZE - rect.h;
+                landed = true;
+            } else {
+                // Jumping: bump our head on the tile above.
+                let (_, row) = self.cell_at(rect.point());
+                rect.y = self.origin.y + (row + 1) as f32 * TILE_SIZE;
+            }
+            body.velocity.y = 0.0;
+        }
+        landed
+    }
+
+    /// Draws the tiles that are inside `view` (the part of the world visible on screen).
+    /// Tiles are drawn one texture at a time so Macroquad can batch them into very few draw calls.
+    pub fn draw(&self, assets: &Assets, view: Rect) {
+        let (first_column, _) = self.cell_at(view.point());
+        let (last_column, _) = self.cell_at(Vec2::new(view.right(), view.y));
+        let last_stored = self.first_column + self.columns.len() as i32 - 1;
+        let visible = first_column.max(self.first_column)..=last_column.min(last_stored);
+
+        for (kind, texture) in [
+            (Tile::Cookie, &assets.cookie_tile),
+            (Tile::Choco, &assets.choco_tile),
+        ] {
+            for column in visible.clone() {
+                for row in 0..TILE_MAP_ROWS as i32 {
+                    if self.tile(column, row) != kind {
+                        continue;
+                    }
+                    texture.draw(
+                        self.origin.x + column as f32 * TILE_SIZE,
+                        self.origin.y + row as f32 * TILE_SIZE,
+                        WHITE,
+                        DrawTextureParams {
+                            dest_size: Some(Vec2::splat(TILE_SIZE)),
+                            ..DrawTextureParams::default()
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// A horizontal stretch of ground or platform the player can stand on.
+/// Surfaces are the nodes of the reachability graph used to check generated levels.
+#[derive(Clone, Copy)]
+pub struct Surface {
+    /// World x position of the left end.
+    pub left: f32,
+    /// World x position of the right end.
+    pub right: f32,
+    /// World y position of the top (where the player's feet rest).
+    pub y: f32,
+    /// The upward speed the player leaves this surface with when jumping.
+    /// Higher than the normal jump speed for bouncy platforms.
+    pub launch_speed: f32,
+}
+
+impl Surface {
+    /// The highest a player jumping from this surface can rise, with the safety factor applied.
+    pub fn max_rise(&self) -> f32 {
+        self.launch_speed * self.launch_speed / (2.0 * GRAVITY) * JUMP_SAFETY_FACTOR
+    }
+
+    /// Returns `true` if a player standing on this surface can jump (or drop) onto `target`,
+    /// given `PLAYER_MOVEMENT_SPEED`, the launch speed and `GRAVITY`.
+    pub fn can_reach(&self, target: &Surface) -> bool {
+        // How much higher the target is. Negative if it is lower.
+        let rise = self.y - target.y;
+        if rise > self.max_rise() {
+            return false;
+        }
+        // Time in the air until the player falls back down to the target's height:
+        // solving `rise = v*t - g*t²/2` for the later of the two times.
+        let v = self.launch_speed;
+        let air_time = (v + (v * v - 2.0 * GRAVITY * rise).max(0.0).sqrt()) / GRAVITY;
+        let reach = PLAYER_MOVEMENT_SPEED * air_time * JUMP_SAFETY_FACTOR;
+        // The horizontal gap between the two surfaces (zero if they overlap).
+        let gap = (target.left - self.right)
+            .max(self.left - target.right)
+            .max(0.0);
+        gap <= reach
+    }
+}
+
+/// Everything that makes up one playable level, built from a seed.
+pub struct Level {
+    /// The ground terrain.
+    pub tile_map: TileMap,
+    /// Everything else in the level: floating platforms, eggs, chickens, spikes and the house.
+    pub world: World,
+    /// The end goal, one of the entities in `world`. Endless levels have no house.
+    pub house: Option<Entity>,
+    /// The x positions of the time-attack checkpoints on the way to the house, left to right.
+    pub checkpoints: Vec<f32>,
+    /// The run times (in seconds) needed for a gold, silver and bronze medal on this level.
+    pub medal_times: [f32; 3],
+    /// Terrain past this x position is kept flat and free of spikes (the area around the house).
+    pub flat_from: f32,
+    /// The height of the last floating platform created, so new ones continue from it.
+    pub last_platform_y: f32,
+    /// Everything left of this x position has been generated.
+    pub generated_to: f32,
+    /// The layers of scenery around the level, from the back to the front.
+    pub parallax: &'static [ParallaxLayer],
+}
+
+impl Level {
+    /// Generates a level that can be completed: the house and at least
+    /// `eggs_needed_for_win` eggs can be reached from the player's start position.
+    /// Tries `seed` first, then the following seeds, and repairs the last attempt if none work.
+    /// `bottom`: The world y position of the bottom of the level.
+    /// `settings`: The difficulty settings controlling how many enemies, spikes and eggs to place.
+    pub fn generate(seed: u64, bottom: f32, settings: &DifficultySettings) -> Level {
+        let eggs_needed = settings.eggs_needed_for_win;
+        let mut level = Level::build(seed, bottom, settings);
+        for attempt in 1..MAX_GENERATION_ATTEMPTS {
+            if level.is_completable(eggs_needed) {
+                return level;
+            }
+            level = Level::build(seed.wrapping_add(attempt), bottom, settings);
+        }
+        if !level.is_completable(eggs_needed) {
+            level.repair(eggs_needed);
+        }
+        // Repairing always works; `tests/level_generation.rs` sweeps many seeds to make sure.
+        debug_assert!(
+            level.is_completable(eggs_needed),
+            "repaired level from seed {seed} can't be completed"
+        );
+        level
+    }
+
+    /// Builds a single level from `seed`, without checking whether it can be completed.
+    pub fn build(seed: u64, bottom: f32, settings: &DifficultySettings) -> Level {
+        // Seeding the random number generator makes the same seed always build the same level.
+        srand(seed);
+
+        // Create the ground out of tiles, keeping the area around the house flat.
+        let mut level = Level::empty(bottom, TERRAIN_END_X - 1000.0);
+        level.tile_map.extend(TERRAIN_END_X, level.flat_from, 0.0);
+        // Fill the level up to the house with platforms, eggs, chickens and spikes.
+        level.populate(0.0, HOUSE_X, settings, 1.0, CHICKEN_BOUNDS);
+
+        // Create the final house structure (the end goal), resting on the ground.
+        // It sits far into the level, past the last platforms.
+        let house_bottom = level.tile_map.surface_y(HOUSE_X).unwrap_or(bottom) - 20.0;
+        level.house = Some(spawn_house(&mut level.world, HOUSE_X, house_bottom));
+
+        // Split the way to the house into equal stretches with checkpoints.
+        let stretch = (HOUSE_X - PLAYER_START_POS.x) / (CHECKPOINT_COUNT + 1) as f32;
+        level.checkpoints = (1..=CHECKPOINT_COUNT)
+            .map(|i| PLAYER_START_POS.x + i as f32 * stretch)
+            .collect();
+        // The ideal run goes straight to the house, with a short detour for each egg needed.
+        let ideal_time = (HOUSE_X - PLAYER_START_POS.x) / PLAYER_MOVEMENT_SPEED
+            + settings.eggs_needed_for_win as f32 * EGG_DETOUR_TIME;
+        level.medal_times = MEDAL_TIME_FACTORS.map(|factor| ideal_time * factor);
+        level
+    }
+
+    /// Starts an endless level from `seed`. It is empty until `extend_endless` adds chunks to it.
+    /// `bottom`: The world y position of the bottom of the level.
+    pub fn endless(seed: u64, bottom: f32) -> Level {
+        srand(seed);
+        // Endless levels never have a flat area for a house.
+        Level {
+            parallax: &ENDLESS_LAYERS,
+            ..Level::empty(bottom, f32::INFINITY)
+        }
+    }
+
+    /// Creates a level with nothing in it yet.
+    pub fn empty(bottom: f32, flat_from: f32) -> Level {
+        Level {
+            tile_map: TileMap::new(TERRAIN_START_X, bottom),
+            world: World::new(),
+            house: None,
+            checkpoints: Vec::new(),
+            medal_times: [f32::INFINITY; 3],
+            flat_from,
+            last_platform_y: gen_range(450.0, 650.0),
+            generated_to: 0.0,
+            parallax: &STORY_LAYERS,
+        }
+    }
+
+    /// The area of the level the camera may show: from the start of the level (x=0) to the end
+    /// of the terrain (endless levels have no end), and up to `LEVEL_HEIGHT` above the bottom.
+    pub fn camera_bounds(&self) -> Rect {
+        let right = if self.house.is_some() {
+            self.tile_map.end_x()
+        } else {
+            f32::INFINITY
+        };
+        let bottom = self.tile_map.bottom();
+        Rect::new(0.0, bottom - LEVEL_HEIGHT, right, LEVEL_HEIGHT)
+    }
+
+    /// Adds the next chunk of an endless level.
+    /// `ramp`: How much harder than the start this chunk is (1.0 = the chosen difficulty).
+    pub fn extend_endless(&mut self, settings: &DifficultySettings, ramp: f32) {
+        let from_x = self.generated_to;
+        let to_x = from_x + ENDLESS_CHUNK_WIDTH;
+        // Build the ground a little further than the chunk, so spikes near its end have ground.
+        self.tile_map
+            .extend(to_x + 500.0, self.flat_from, (ramp - 1.0) * 0.15);
+        // Let the chunk's chickens roam a bit beyond it on either side.
+        let chicken_bounds = Rect::new(
+            from_x - 500.0,
+            CHICKEN_BOUNDS.y,
+            ENDLESS_CHUNK_WIDTH + 1500.0,
+            CHICKEN_BOUNDS.h,
+        );
+        self.populate(from_x, to_x, settings, ramp, chicken_bounds);
+    }
+
+    /// Fills the stretch of level between `from_x` and `to_x` with floating platforms,
+    /// eggs, chickens and spikes. The tile map must already cover this stretch.
+    /// `ramp`: Multiplier for the number of chickens, their speed and the chance of spikes.
+    /// `chicken_bounds`: The area the new chickens are allowed to fly in.
+    pub fn populate(
+        &mut self,
+        from_x: f32,
+        to_x: f32,
+        settings: &DifficultySettings,
+        ramp: f32,
+        chicken_bounds: Rect,
+    ) {
+        // Create floating platforms. Each platform's height is a small random step up or down
+        // from the previous one, so neighbouring platforms stay within jumping distance.
+        let platform_count = ((to_x - from_x) / PLATFORM_SPACING) as usize;
+        let mut new_platforms = Vec::with_capacity(platform_count);
+        for i in 0..platform_count {
+            // Calculate x position with some randomness.
+            let x = from_x + i as f32 * PLATFORM_SPACING + gen_range(-200.0, 200.0);
+            // Step up or down from the previous platform, staying within a range.
+            let y = (self.last_platform_y + gen_range(-90.0, 90.0)).clamp(150.0, 650.0);
+            self.last_platform_y = y;
+            // Pick a random kind; its size depends on the texture it uses.
+            let kind = PlatformKind::choose(settings.platform_kind_weights);
+            let size = kind.size();
+            // Only static platforms hold eggs, otherwise eggs would be left floating in the air.
+            let holds_eggs = matches!(kind, PlatformKind::Static);
+            let position = Vec2::new(
+                x - size.x / 2.0, // Center horizontally
+                y - size.y / 2.0, // Center vertically
+            );
+            spawn_platform(&mut self.world, position, size, kind);
+            new_platforms.push((
+                Rect::new(position.x, position.y, size.x, size.y),
+                holds_eggs,
+            ));
+        }
+
+        // Create eggs, placing them on top of some of the new platforms.
+        for (platform, _) in new_platforms.iter().filter(|(_, holds_eggs)| *holds_eggs) {
+            // Keep only some platforms to spawn an egg on.
+            if gen_range(0, 100) >= settings.egg_chance {
+                continue;
+            }
+            // Create an egg somewhere along the platform's middle half.
+            let offset = gen_range(-0.25, 0.25) * platform.w;
+            let x = platform.center().x + offset; // Position egg horizontally on platform
+            let y = platform.y - EGG_SIZE.y + 5.0; // Position egg just above the platform surface
+            spawn_egg(&mut self.world, x, y);
+        }
+
+        // Create flying chickens with random starting positions, velocities and behaviours.
+        // The difficulty's chicken count is for a full story level; shorter stretches get fewer.
+        let chicken_count =
+            (settings.chicken_count as f32 * (to_x - from_x) / HOUSE_X * ramp).round() as u32;
+        for _ in 0..chicken_count {
+            // Random horizontal position within a wide range of the game world.
+            let x = gen_range(from_x + 500.0, to_x + 1000.0);
+            // Random vertical position within the typical play area.
+            let y = gen_range(100.0, 600.0);
+
+            // Random horizontal speed, can be left or right.
+            let vx = gen_range(50.0, 150.0) * (if gen_range(0, 2) == 0 { 1.0 } else { -1.0 });
+            // Random vertical speed, can be up or down.
+            let vy = gen_range(30.0, 80.0) * (if gen_range(0, 2) == 0 { 1.0 } else { -1.0 });
+            // Some chickens are tougher and need to be stomped more than once.
+            let health = if gen_range(0, 100) < settings.tough_chicken_chance {
+                TOUGH_CHICKEN_HEALTH
+            } else {
+                CHICKEN_HEALTH
+            };
+
+            let chicken = Chicken {
+                // Each chicken gets its own personality.
+                behaviour: ChickenBehaviour::choose(
+                    Vec2::new(x, y),
+                    settings.chicken_behaviour_weights,
+                ),
+                // Chickens get faster as the ramp goes up, but never more than twice as fast.
+                speed_scale: settings.chicken_speed * ramp.min(2.0),
+                bounds: chicken_bounds,
+            };
+            spawn_chicken(
+                &mut self.world,
+                Vec2::new(x, y),
+                Vec2::new(vx, vy),
+                health,
+                chicken,
+            );
+        }
+
+        // Create spikes, placing them on top of the ground every now and then.
+        let spike_chance = (settings.spike_chance as f32 * ramp) as i32;
+        let first_spot = from_x.max(SAFE_START_X) as i32;
+        let last_spot = to_x.min(self.flat_from) as i32;
+        // Consider a spot every 400 units.
+        for x in (first_spot..last_spot).step_by(400).map(|x| x as f32) {
+            // Only place spikes randomly, and never over a pit.
+            if gen_range(0, 100) >= spike_chance {
+                continue;
+            }
+            if let Some(surface_y) = self.tile_map.surface_y(x) {
+                spawn_spike(&mut self.world, x, surface_y);
+            }
+        }
+
+        self.generated_to = to_x;
+    }
+
+    /// Removes everything lying completely left of world position `x`,
+    /// so endless levels don't keep growing in memory.
+    pub fn discard_before(&mut self, x: f32) {
+        self.tile_map.discard_before(x);
+        let world = &self.world;
+        let behind: Vec<Entity> = world
+            .transforms
+            .iter()
+            .filter(|(entity, transform)| {
+                let right = match world.behaviours.get(*entity) {
+                    // Moving platforms are judged by their starting point
+                    // so they aren't removed mid-route.
+                    Some(Behaviour::Platform(platform)) => {
+                        platform.origin.x + transform.rect.w + MOVING_PLATFORM_RANGE
+                    }
+                    _ => transform.rect.right(),
+                };
+                right < x
+            })
+            .map(|(entity, _)| entity)
+            .collect();
+        // Anything still holding on to a removed entity (a player standing on a platform,
+        // a chicken heading for one) simply stops finding it.
+        for entity in behind {
+            self.world.despawn(entity);
+        }
+    }
+
+    /// The rectangles of every egg that can still be collected.
+    pub fn egg_rects(&self) -> Vec<Rect> {
+        self.world
+            .pickups
+            .iter()
+            .filter(|(_, pickup)| pickup.eggs > 0)
+            .filter_map(|(entity, _)| self.world.transforms.get(entity))
+            .map(|transform| transform.rect)
+            .collect()
+    }
+
+    /// Collects every surface the player could stand on, with spikes cut out of them.
+    pub fn surfaces(&self) -> Vec<Surface> {
+        let mut surfaces: Vec<Surface> = Vec::new();
+
+        // --- Ground ---
+        // A tile is standable if it is solid and the tile above it is empty.
+        // Neighbouring columns at the same height are merged into one surface.
+        let map = &self.tile_map;
+        let stored_columns = map.first_column..map.first_column + map.columns.len() as i32;
+        for column in stored_columns {
+            let left = map.origin.x + column as f32 * TILE_SIZE;
+            for row in 0..TILE_MAP_ROWS as i32 {
+                if !map.tile(column, row).is_solid() || map.tile(column, row - 1).is_solid() {
+                    continue;
+                }
+                let y = map.origin.y + row as f32 * TILE_SIZE;
+                match surfaces
+                    .iter_mut()
+                    .find(|surface| surface.y == y && surface.right == left)
+                {
+                    Some(surface) => surface.right += TILE_SIZE,
+                    None => surfaces.push(Surface {
+                        left,
+                        right: left + TILE_SIZE,
+                        y,
+                        launch_speed: PLAYER_JUMP_SPEED,
+                    }),
+                }
+            }
+        }
+
+        // --- Platforms ---
+        for (_, rect, platform) in platforms(&self.world) {
+            let (mut left, mut right) = (rect.x, rect.right());
+            let launch_speed = match platform.kind {
+                // Sideways moving platforms cover their whole route.
+                PlatformKind::Moving { direction, .. } if direction.x != 0.0 => {
+                    left -= MOVING_PLATFORM_RANGE;
+                    right += MOVING_PLATFORM_RANGE;
+                    PLAYER_JUMP_SPEED
+                }
+                PlatformKind::Bouncy => BOUNCY_PLATFORM_SPEED,
+                _ => PLAYER_JUMP_SPEED,
+            };
+            surfaces.push(Surface {
+                left,
+                right,
+                y: rect.y,
+                launch_speed,
+            });
+        }
+
+        // --- Spikes ---
+        // Standing next to a spike is fine, standing on it isn't: split surfaces around spikes.
+        let spike_rects: Vec<Rect> = self
+            .world
+            .hazards
+            .iter()
+            .filter(|(_, hazard)| matches!(hazard, Hazard::Sharp))
+            .filter_map(|(entity, _)| self.world.collision_bounds(entity))
+            .collect();
+        for spike_rect in spike_rects {
+            let mut split = Vec::with_capacity(surfaces.len() + 1);
+            for surface in surfaces {
+                let touches_spike = (spike_rect.bottom() - surface.y).abs() < TILE_SIZE / 2.0
+                    && spike_rect.x < surface.right
+                    && spike_rect.right() > surface.left;
+                if touches_spike {
+                    split.push(Surface {
+                        right: spike_rect.x,
+                        ..surface
+                    });
+                    split.push(Surface {
+                        left: spike_rect.right(),
+                        ..surface
+                    });
+                } else {
+                    split.push(surface);
+                }
+            }
+            surfaces = split;
+        }
+
+        // Surfaces narrower than the player can't be stood on.
+        surfaces.retain(|surface| surface.right - surface.left >= PLAYER_SIZE.x);
+        surfaces
+    }
+
+    /// Finds every surface the player can get to from the start position,
+    /// by walking the jump-reachability graph breadth first.
+    pub fn reachable_surfaces(&self) -> Vec<Surface> {
+        let surfaces = self.surfaces();
+
+        // The player starts by falling onto the highest surface below the start position.
+        let start = surfaces
+            .iter()
+            .enumerate()
+            .filter(|(_, surface)| {
+                surface.left <= PLAYER_START_POS.x
+                    && surface.right >= PLAYER_START_POS.x
+                    && surface.y >= PLAYER_START_POS.y
+            })
+            .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y))
+            .map(|(index, _)| index);
+
+        let mut reached = vec![false; surfaces.len()];
+        let mut queue: Vec<usize> = start.into_iter().collect();
+        for &index in &queue {
+            reached[index] = true;
+        }
+        while let Some(from) = queue.pop() {
+            for (to, target) in surfaces.iter().enumerate() {
+                if !reached[to] && surfaces[from].can_reach(target) {
+                    reached[to] = true;
+                    queue.push(to);
+                }
+            }
+        }
+
+        surfaces
+            .into_iter()
+            .zip(reached)
+            .filter(|(_, reached)| *reached)
+            .map(|(surface, _)| surface)
+            .collect()
+    }
+
+    /// Returns `true` if the player can touch `rect` from one of the `surfaces`,
+    /// either by standing next to it or by jumping up to it.
+    pub fn can_touch(surfaces: &[Surface], rect: Rect) -> bool {
+        surfaces.iter().any(|surface| {
+            rect.x < surface.right
+                && rect.right() > surface.left
+                && rect.bottom() > surface.y - surface.max_rise() - PLAYER_SIZE.y
+                && rect.y < surface.y
+        })
+    }
+
+    /// Returns `true` if the house (if there is one) and at least `eggs_needed` eggs can be reached.
+    pub fn is_completable(&self, eggs_needed: u32) -> bool {
+        let reachable = self.reachable_surfaces();
+        let reachable_eggs = self
+            .egg_rects()
+            .into_iter()
+            .filter(|egg| Level::can_touch(&reachable, *egg))
+            .count();
+        let house_reachable = self.house.is_none_or(|house| {
+            self.world
+                .transforms
+                .get(house)
+                .is_some_and(|house| Level::can_touch(&reachable, house.rect))
+        });
+        house_reachable && reachable_eggs >= eggs_needed as usize
+    }
+
+    /// Makes a level completable when no seed produced one:
+    /// fills in pits, removes spikes and lays extra eggs along the ground.
+    /// If the house still can't be reached, or there isn't room for enough eggs (say, the way
+    /// is blocked by tunnels too tall to climb), all the ground is made flat, which can always
+    /// be walked along.
+    pub fn repair(&mut self, eggs_needed: u32) {
+        // Fill every pit with flat ground so the house can be walked to.
+        for column in &mut self.tile_map.columns {
+            if column.iter().all(|tile| !tile.is_solid()) {
+                *column = TileMap::ground_column(GROUND_HEIGHT);
+            }
+        }
+        let spikes: Vec<Entity> = self
+            .world
+            .hazards
+            .iter()
+            .filter(|(_, hazard)| matches!(hazard, Hazard::Sharp))
+            .map(|(entity, _)| entity)
+            .collect();
+        for spike in spikes {
+            self.world.despawn(spike);
+        }
+
+        if !self.is_completable(0) || !self.lay_ground_eggs(eggs_needed) {
+            // The house already stands on flat ground, so it doesn't need to move.
+            for column in &mut self.tile_map.columns {
+                *column = TileMap::ground_column(GROUND_HEIGHT);
+            }
+            self.lay_ground_eggs(eggs_needed);
+        }
+    }
+
+    /// Lays eggs on the ground between the start and the house, only where the player can
+    /// reach them, until at least `eggs_needed` eggs can be reached.
+    /// Returns `false` if it ran out of room first.
+    fn lay_ground_eggs(&mut self, eggs_needed: u32) -> bool {
+        let reachable = self.reachable_surfaces();
+        let mut eggs = self.egg_rects();
+        let mut reachable_eggs = eggs
+            .iter()
+            .filter(|egg| Level::can_touch(&reachable, **egg))
+            .count();
+        // The gap between the eggs leaves room for the most eggs any difficulty asks for.
+        let mut x = SAFE_START_X;
+        while reachable_eggs < eggs_needed as usize && x < HOUSE_X {
+            if let Some(surface_y) = self.tile_map.surface_y(x) {
+                let y = surface_y - EGG_SIZE.y;
+                let egg = Rect::new(x - EGG_SIZE.x / 2.0, y, EGG_SIZE.x, EGG_SIZE.y);
+                // Skip spots the player can't get to (like the top of a tunnel), and spots
+                // where an egg already lies (from an earlier try).
+                if Level::can_touch(&reachable, egg)
+                    && !eggs.iter().any(|other| other.overlaps(&egg))
+                {
+                    spawn_egg(&mut self.world, x, y);
+                    eggs.push(egg);
+                    reachable_eggs += 1;
+                }
+            }
+            x += REPAIR_EGG_SPACING;
+        }
+        reachable_eggs >= eggs_needed as usize
+    }
+}
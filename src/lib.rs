@@ -0,0 +1,93 @@
+//! Easter Egg: a platformer where a rabbit steals eggs from chickens and runs for home.
+//!
+//! The game is a library so that other programs can reuse it: the game binary, the headless
+//! race server (`race_server`), tools and tests all build on the modules below.
+//!
+//! The tests in `tests/` use the library from outside, just like those programs do:
+//! `level_generation` checks that thousands of seeds give levels that can be completed, and
+//! `race_loopback` runs races between several clients and a race server on loopback.
+//! None of them needs a window, so `cargo test` runs anywhere.
+
+// These lines disable certain warnings from Clippy, a Rust linter.
+// Useful for focusing on core logic, but good to address these in larger projects.
+#![allow(clippy::pedantic, clippy::nursery, clippy::manual_range_contains)]
+
+pub mod assets;
+pub mod atlas;
+pub mod audio;
+pub mod camera;
+pub mod captions;
+pub mod constants;
+pub mod display;
+pub mod entities;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
+pub mod level;
+pub mod locale;
+pub mod net;
+pub mod options;
+pub mod parallax;
+pub mod particles;
+pub mod physics;
+pub mod scenes;
+pub mod scores;
+pub mod screens;
+pub mod settings;
+pub mod text;
+pub mod world;
+
+use crate::assets::load_assets;
+use crate::constants::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::display::{Display, Scaling};
+use crate::scenes::{SceneContext, SceneStack};
+use crate::scores::HighScores;
+use crate::screens::start::StartScreen;
+use crate::settings::Settings;
+use macroquad::prelude::*;
+use std::path::PathBuf;
+
+/// Configures the game window settings.
+/// The window is opened before the game runs, so a language changed in game only renames the
+/// window the next time the game starts.
+pub fn window_conf() -> Conf {
+    let strings = Settings::load().strings();
+    Conf {
+        window_title: strings.window_title.to_owned(), // Title shown in the window bar
+        window_width: VIRTUAL_WIDTH as i32,            // Initial width in pixels
+        window_height: VIRTUAL_HEIGHT as i32,          // Initial height in pixels
+        ..Default::default() // Use default values for other configuration options
+    }
+}
+
+/// Runs the game: loads the assets, then runs the scenes, starting from the start screen.
+/// Each scene decides which comes next (a round, the game over screen, a pause menu, ...).
+/// `server_address`: The race server to join when the Race mode is chosen.
+/// `asset_packs`: Directories of replacement art and sounds to use, in order. Later packs win.
+/// `scaling`: How the virtual screen the game is drawn on is scaled up to fill the window.
+pub async fn run_game(server_address: String, asset_packs: Vec<PathBuf>, scaling: Scaling) {
+    // Everything is drawn on a virtual screen of a fixed size, scaled up to fit the window.
+    let display = Display::new(scaling);
+    // Load all assets before starting the game, showing a loading screen meanwhile.
+    // `.await` is used because `load_assets` is an async function.
+    let (assets, errors) = load_assets(&display, &asset_packs).await;
+    // Anything that failed to load has a stand-in, so just report it and carry on.
+    for error in &errors {
+        eprintln!("Warning: {error}");
+    }
+
+    // Everything the scenes share, including the saved high scores,
+    // so new scores can be compared against them, and the saved settings.
+    let mut context = SceneContext {
+        assets,
+        high_scores: HighScores::load(),
+        settings: Settings::load(),
+        server_address,
+        display,
+        #[cfg(feature = "hot-reload")]
+        hot_reloader: hot_reload::HotReloader::new(&asset_packs),
+    };
+
+    // Show the start screen, and run whichever scenes follow until the last one is gone.
+    let mut scenes = SceneStack::new(Box::new(StartScreen::new()), &mut context);
+    scenes.run(&mut context).await;
+}
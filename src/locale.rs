@@ -0,0 +1,289 @@
+//! The languages the game can be played in, and the words it shows in each of them.
+//!
+//! Every piece of text the player sees comes from a `Strings` table, one per language, so
+//! translating the game means filling in one more table. Lines with numbers or names in them
+//! are templates: each "{}" is replaced, in order, by `fill`.
+
+use std::fmt;
+
+/// The languages the game can be played in.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Language {
+    English,
+    Portuguese,
+}
+
+impl Language {
+    /// All languages, in the order they are cycled through on the options screen.
+    pub const ALL: [Language; 2] = [Language::English, Language::Portuguese];
+
+    /// The language's own name for itself, so players can find theirs in the list.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Portuguese => "Português",
+        }
+    }
+
+    /// The short code saved in the settings file and used in asset pack names, e.g. "pt".
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Portuguese => "pt",
+        }
+    }
+
+    /// Finds the language with the given code, as saved in the settings file.
+    pub fn from_code(code: &str) -> Option<Language> {
+        Language::ALL
+            .into_iter()
+            .find(|language| language.code() == code)
+    }
+
+    /// The language the computer is set to (from the `LANG` environment variable, e.g.
+    /// "pt_BR.UTF-8"), or English if the game doesn't speak it.
+    pub fn from_environment() -> Language {
+        std::env::var("LANG")
+            .ok()
+            .and_then(|lang| Language::from_code(lang.get(..2)?))
+            .unwrap_or(Language::English)
+    }
+
+    /// The words the game shows in this language.
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Language::English => &ENGLISH,
+            Language::Portuguese => &PORTUGUESE,
+        }
+    }
+}
+
+/// Every piece of text the game shows, in one language.
+pub struct Strings {
+    /// The title of the game window.
+    pub window_title: &'static str,
+
+    // --- Start Screen ---
+    /// The chosen players, e.g. "Players: 1 Player (C to change)".
+    pub players_choice: &'static str,
+    /// The chosen mode, e.g. "Mode: Story (M to change)".
+    pub mode_choice: &'static str,
+    /// How to open the options screen.
+    pub options_hint: &'static str,
+    /// The names of the player choices, in the order of `Players::ALL`.
+    pub players: [&'static str; 3],
+    /// The names of the modes, in the order of `GameMode::ALL`.
+    pub modes: [&'static str; 4],
+    /// The names of the difficulties, in the order of `Difficulty::ALL`.
+    pub difficulties: [&'static str; 4],
+    /// The names of the Custom difficulty's settings, in the order of `custom_fields`.
+    pub custom_fields: [&'static str; 6],
+
+    // --- Options Screen ---
+    /// The heading of the options screen.
+    pub options_title: &'static str,
+    /// How to use the options screen.
+    pub options_help: &'static str,
+    /// The names of the settings, in the order of `Settings::fields`.
+    pub setting_names: [&'static str; 5],
+    /// Shown under the game speed setting, since it doesn't apply to every mode.
+    pub game_speed_note: &'static str,
+    /// The values of settings that are switched on or off.
+    pub on: &'static str,
+    pub off: &'static str,
+
+    // --- In Game ---
+    /// A single player's score, e.g. "Score: 12".
+    pub score: &'static str,
+    /// One of two players' scores, e.g. "P1 Score: 12".
+    pub player_score: &'static str,
+    /// The lives left, e.g. "Lives: 3".
+    pub lives: &'static str,
+    /// Shown instead of the lives once a player has none left.
+    pub out: &'static str,
+    /// The time a checkpoint was passed, e.g. "Split 2: 0:31.20".
+    pub split: &'static str,
+    /// The heading of the pause menu.
+    pub paused: &'static str,
+    /// How to leave the pause menu.
+    pub resume_hint: &'static str,
+    /// What each sound sounds like, shown as a caption when it plays,
+    /// in the order of `Cue::ALL`.
+    pub captions: [&'static str; 7],
+
+    // --- Online Race ---
+    /// Shown while joining a race, e.g. "Connecting to 127.0.0.1:7777...".
+    pub connecting: &'static str,
+    /// How many are racing, e.g. "Online race: 3 racers".
+    pub racers: &'static str,
+    /// What to do in a race.
+    pub race_goal: &'static str,
+    /// How to leave a race, or stop joining one.
+    pub leave_race_hint: &'static str,
+    /// Why a race ended early: the server couldn't be reached (the address and the error).
+    pub join_failed: &'static str,
+    /// Why a race ended early: the connection broke (the error).
+    pub connection_lost: &'static str,
+    /// Why a race ended early: the server went quiet.
+    pub server_silent: &'static str,
+
+    // --- Game Over Screen ---
+    /// The score after dying, e.g. "Final Score: 12".
+    pub final_score: &'static str,
+    /// The winner of a versus game and every score, e.g. "Player 2 wins!  P1: 4  P2: 9".
+    pub player_wins: &'static str,
+    /// One player's score in the versus results, e.g. "P1: 4".
+    pub player_short_score: &'static str,
+    /// The place a race was finished in, e.g. "Finished the race in place 2 of 3".
+    pub race_place: &'static str,
+    /// A time-attack run's time and medal, e.g. "Time: 0:45.67  Gold".
+    pub run_time: &'static str,
+    /// The times each checkpoint was passed, e.g. "Splits: 0:12.40  0:31.20".
+    pub splits: &'static str,
+    /// The names of the medals, in the order of `Medal::ALL`.
+    pub medals: [&'static str; 3],
+    /// Shown in place of a medal when the run wasn't fast enough for one.
+    pub no_medal: &'static str,
+}
+
+/// The game in English.
+pub const ENGLISH: Strings = Strings {
+    window_title: "Easter Egg",
+    players_choice: "Players: {} (C to change)",
+    mode_choice: "Mode: {} (M to change)",
+    options_hint: "O: Options",
+    players: ["1 Player", "2 Players Co-op", "2 Players Versus"],
+    modes: ["Story", "Endless", "Time Attack", "Race"],
+    difficulties: ["Easy", "Normal", "Hard", "Custom"],
+    custom_fields: [
+        "Chickens",
+        "Chicken speed",
+        "Spike chance",
+        "Egg chance",
+        "Lives",
+        "Eggs to win",
+    ],
+    options_title: "Options",
+    options_help: "Up/Down: choose   Left/Right: change   Escape: back",
+    setting_names: [
+        "Language",
+        "High contrast",
+        "Reduced motion",
+        "Game speed",
+        "Captions",
+    ],
+    game_speed_note: "Online races always run at full speed",
+    on: "On",
+    off: "Off",
+    score: "Score: {}",
+    player_score: "P{} Score: {}",
+    lives: "Lives: {}",
+    out: "Out",
+    split: "Split {}: {}",
+    paused: "Paused",
+    resume_hint: "Press Escape to carry on",
+    captions: [
+        "Boing (jump)",
+        "{egg} Egg collected",
+        "{chicken} Chicken squawks",
+        "{spike} Ouch, spikes!",
+        "Magic chime",
+        "Splash! Game over",
+        "Victory fanfare",
+    ],
+    connecting: "Connecting to {}...",
+    racers: "Online race: {} racers",
+    race_goal: "Be the first to reach the house!",
+    leave_race_hint: "Press Escape to leave the race",
+    join_failed: "Could not join the race at {}: {}",
+    connection_lost: "Lost the connection to the race: {}",
+    server_silent: "The race server stopped answering",
+    final_score: "Final Score: {}",
+    player_wins: "Player {} wins!  {}",
+    player_short_score: "P{}: {}",
+    race_place: "Finished the race in place {} of {}",
+    run_time: "Time: {}  {}",
+    splits: "Splits: {}",
+    medals: ["Gold", "Silver", "Bronze"],
+    no_medal: "No medal",
+};
+
+/// The game in (Brazilian) Portuguese.
+pub const PORTUGUESE: Strings = Strings {
+    window_title: "Ovo de Páscoa",
+    players_choice: "Jogadores: {} (C para mudar)",
+    mode_choice: "Modo: {} (M para mudar)",
+    options_hint: "O: Opções",
+    players: ["1 Jogador", "2 Jogadores Cooperativo", "2 Jogadores Versus"],
+    modes: ["História", "Infinito", "Contra o Relógio", "Corrida"],
+    difficulties: ["Fácil", "Normal", "Difícil", "Personalizado"],
+    custom_fields: [
+        "Galinhas",
+        "Velocidade das galinhas",
+        "Chance de espinhos",
+        "Chance de ovos",
+        "Vidas",
+        "Ovos para vencer",
+    ],
+    options_title: "Opções",
+    options_help: "Cima/Baixo: escolher   Esquerda/Direita: mudar   Esc: voltar",
+    setting_names: [
+        "Idioma",
+        "Alto contraste",
+        "Menos movimento",
+        "Velocidade do jogo",
+        "Legendas",
+    ],
+    game_speed_note: "Corridas online sempre rodam a toda velocidade",
+    on: "Sim",
+    off: "Não",
+    score: "Pontos: {}",
+    player_score: "J{} Pontos: {}",
+    lives: "Vidas: {}",
+    out: "Fora",
+    split: "Parcial {}: {}",
+    paused: "Pausado",
+    resume_hint: "Aperte Esc para continuar",
+    captions: [
+        "Boing (pulo)",
+        "{egg} Ovo coletado",
+        "{chicken} Galinha cacareja",
+        "{spike} Ai, espinhos!",
+        "Som mágico",
+        "Splash! Fim de jogo",
+        "Fanfarra de vitória",
+    ],
+    connecting: "Conectando a {}...",
+    racers: "Corrida online: {} corredores",
+    race_goal: "Seja o primeiro a chegar à casa!",
+    leave_race_hint: "Aperte Esc para sair da corrida",
+    join_failed: "Não foi possível entrar na corrida em {}: {}",
+    connection_lost: "A conexão com a corrida caiu: {}",
+    server_silent: "O servidor da corrida parou de responder",
+    final_score: "Pontuação Final: {}",
+    player_wins: "Jogador {} venceu!  {}",
+    player_short_score: "J{}: {}",
+    race_place: "Terminou a corrida em {}º lugar de {}",
+    run_time: "Tempo: {}  {}",
+    splits: "Parciais: {}",
+    medals: ["Ouro", "Prata", "Bronze"],
+    no_medal: "Sem medalha",
+};
+
+/// Fills in a template from a `Strings` table, replacing each "{}" with the next value.
+/// `template`: The text to fill in, e.g. "Lives: {}".
+/// `values`: What goes in each "{}", in order. Missing values leave the "{}" empty.
+pub fn fill(template: &str, values: &[&dyn fmt::Display]) -> String {
+    let mut filled = String::new();
+    let mut values = values.iter();
+    let mut parts = template.split("{}").peekable();
+    while let Some(part) = parts.next() {
+        filled.push_str(part);
+        if parts.peek().is_some() {
+            if let Some(value) = values.next() {
+                filled.push_str(&value.to_string());
+            }
+        }
+    }
+    filled
+}
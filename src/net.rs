@@ -0,0 +1,811 @@
+//! Online races: the messages sent over UDP, the authoritative race server,
+//! and a game's connection to it.
+
+use crate::constants::*;
+use crate::entities::platform::{platform_system, PlatformKind};
+use crate::entities::player::{Controls, Player, PlayerInput};
+use crate::level::Level;
+use crate::options::DifficultySettings;
+use crate::parallax::RACE_LAYERS;
+use crate::world::components::Behaviour;
+use crate::world::Entity;
+use macroquad::miniquad::date;
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// Builds the level used for online races from the server's seed.
+/// Every racer builds the same level, so only the players need to be sent over the network.
+/// Races are about speed, so they have no eggs or chickens.
+/// Crumbling platforms are made solid for good: they fall when a racer stands on them, which
+/// only the server would see, so every other racer's copy of the level would be wrong.
+/// The other platforms follow the race clock alone, which every racer shares.
+pub fn race_level(seed: u64) -> Level {
+    let mut level = Level::generate(seed, RACE_LEVEL_BOTTOM, &DifficultySettings::NORMAL);
+    let world = &level.world;
+    let removed: Vec<Entity> = world
+        .pickups
+        .iter()
+        .map(|(entity, _)| entity)
+        .chain(
+            world
+                .behaviours
+                .iter()
+                .filter(|(_, behaviour)| matches!(behaviour, Behaviour::Chicken(_)))
+                .map(|(entity, _)| entity),
+        )
+        .collect();
+    for entity in removed {
+        level.world.despawn(entity);
+    }
+    for (_, behaviour) in level.world.behaviours.iter_mut() {
+        if let Behaviour::Platform(platform) = behaviour {
+            if matches!(platform.kind, PlatformKind::Crumbling(_)) {
+                platform.kind = PlatformKind::Static;
+            }
+        }
+    }
+    level.parallax = &RACE_LAYERS;
+    level
+}
+
+/// One racer as seen by the race server.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RacerState {
+    /// The id the server gave this racer when they joined.
+    pub player_id: u8,
+    /// The top-left corner of the racer's character.
+    pub position: Vec2,
+    /// The racer's velocity, used for prediction and to pick the sprite's facing direction.
+    pub velocity: Vec2,
+    /// The place the racer finished in (1 = first), or 0 while still racing.
+    pub place: u8,
+}
+
+/// The messages sent between race clients and the race server, one per UDP packet.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Packet {
+    /// Client -> server: asks to join the race.
+    Join,
+    /// Server -> client: accepts the client, with their player id and the level's seed.
+    Welcome { player_id: u8, seed: u64 },
+    /// Client -> server: the buttons held during one frame, numbered so the server
+    /// can say which inputs it has applied.
+    Input {
+        sequence: u32,
+        input: PlayerInput,
+        delta_time: f32,
+    },
+    /// Server -> client: every racer's state, the last input the server applied for the
+    /// client receiving it, and how long (in seconds) the server has been moving the level's
+    /// platforms, so clients can keep theirs in step.
+    State {
+        last_input: u32,
+        race_time: f64,
+        racers: Vec<RacerState>,
+    },
+    /// Client -> server: leaves the race.
+    Leave,
+}
+
+/// Reads little-endian values from the front of a received packet.
+struct PacketReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl PacketReader<'_> {
+    /// Takes the next `N` bytes, or `None` if the packet is too short.
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (value, rest) = self.bytes.split_first_chunk::<N>()?;
+        self.bytes = rest;
+        Some(*value)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|[byte]| byte)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    /// Reads a number, rejecting NaN and infinity: no sensible packet holds them, and a
+    /// single one would poison every calculation it reaches.
+    fn f32(&mut self) -> Option<f32> {
+        self.take()
+            .map(f32::from_le_bytes)
+            .filter(|value| value.is_finite())
+    }
+
+    /// Reads a number, rejecting NaN and infinity like `f32` does.
+    fn f64(&mut self) -> Option<f64> {
+        self.take()
+            .map(f64::from_le_bytes)
+            .filter(|value| value.is_finite())
+    }
+
+    fn vec2(&mut self) -> Option<Vec2> {
+        Some(Vec2::new(self.f32()?, self.f32()?))
+    }
+}
+
+impl Packet {
+    /// Turns the packet into bytes: a tag byte saying which message it is,
+    /// followed by its fields in little-endian order.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match self {
+            Packet::Join => bytes.push(0),
+            Packet::Welcome { player_id, seed } => {
+                bytes.push(1);
+                bytes.push(*player_id);
+                bytes.extend_from_slice(&seed.to_le_bytes());
+            }
+            Packet::Input {
+                sequence,
+                input,
+                delta_time,
+            } => {
+                bytes.push(2);
+                bytes.extend_from_slice(&sequence.to_le_bytes());
+                // The three buttons are packed into the bits of a single byte.
+                bytes.push(input.left as u8 | (input.right as u8) << 1 | (input.jump as u8) << 2);
+                bytes.extend_from_slice(&delta_time.to_le_bytes());
+            }
+            Packet::State {
+                last_input,
+                race_time,
+                racers,
+            } => {
+                bytes.push(3);
+                bytes.extend_from_slice(&last_input.to_le_bytes());
+                bytes.extend_from_slice(&race_time.to_le_bytes());
+                bytes.push(racers.len() as u8);
+                for racer in racers {
+                    bytes.push(racer.player_id);
+                    for value in [
+                        racer.position.x,
+                        racer.position.y,
+                        racer.velocity.x,
+                        racer.velocity.y,
+                    ] {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                    bytes.push(racer.place);
+                }
+            }
+            Packet::Leave => bytes.push(4),
+        }
+        bytes
+    }
+
+    /// Reads a packet from received bytes. Returns `None` for anything malformed,
+    /// so stray or corrupted packets are simply ignored.
+    pub fn decode(bytes: &[u8]) -> Option<Packet> {
+        let mut reader = PacketReader { bytes };
+        let packet = match reader.u8()? {
+            0 => Packet::Join,
+            1 => Packet::Welcome {
+                player_id: reader.u8()?,
+                seed: reader.u64()?,
+            },
+            2 => {
+                let sequence = reader.u32()?;
+                let buttons = reader.u8()?;
+                Packet::Input {
+                    sequence,
+                    input: PlayerInput {
+                        left: buttons & 1 != 0,
+                        right: buttons & 2 != 0,
+                        jump: buttons & 4 != 0,
+                    },
+                    delta_time: reader.f32()?,
+                }
+            }
+            3 => {
+                let last_input = reader.u32()?;
+                let race_time = reader.f64()?;
+                let count = reader.u8()?;
+                let racers = (0..count)
+                    .map(|_| {
+                        Some(RacerState {
+                            player_id: reader.u8()?,
+                            position: reader.vec2()?,
+                            velocity: reader.vec2()?,
+                            place: reader.u8()?,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Packet::State {
+                    last_input,
+                    race_time,
+                    racers,
+                }
+            }
+            4 => Packet::Leave,
+            _ => return None,
+        };
+        Some(packet)
+    }
+}
+
+/// A racer connected to the race server.
+struct RaceClient {
+    /// Where the client's packets come from and where its updates are sent.
+    address: SocketAddr,
+    /// The racer's id, shared with every client.
+    player_id: u8,
+    /// The racer's character, moved only by the server.
+    player: Player,
+    /// The sequence number of the last input applied.
+    last_input: u32,
+    /// When the server last heard from the client, to drop clients that went away.
+    last_heard: Instant,
+    /// How many seconds of play the client's inputs may still move the racer by. It fills up
+    /// as real time passes, so a client can't run its racer faster than the server's clock.
+    time_budget: f32,
+    /// When `time_budget` was last filled up.
+    budget_filled: Instant,
+    /// The place the racer finished in (1 = first), or 0 while still racing.
+    place: u8,
+}
+
+/// The authoritative race server. It runs every racer's physics from the inputs they send,
+/// and regularly tells every client where everyone is. It needs no window or graphics,
+/// so it can run headless, for example on loopback next to the game for testing.
+pub struct RaceServer {
+    socket: UdpSocket,
+    /// The seed of the level being raced, sent to every client that joins.
+    seed: u64,
+    /// The level being raced, used to run the racers' physics.
+    level: Level,
+    /// Builds the level for each race from its seed. Usually `race_level`.
+    build_level: fn(u64) -> Level,
+    /// How long (in seconds) the level's platforms have been moving. Sent to every client,
+    /// whose platforms follow this clock instead of their own frame times.
+    race_time: f64,
+    clients: Vec<RaceClient>,
+    /// How many racers have reached the house so far.
+    finished: u8,
+}
+
+impl RaceServer {
+    /// Starts a race server listening on `address`, e.g. "127.0.0.1:7777".
+    /// Use port 0 to let the operating system pick a free port (see `local_addr`).
+    pub fn bind(address: &str) -> std::io::Result<RaceServer> {
+        RaceServer::bind_with_levels(address, race_level)
+    }
+
+    /// Starts a race server like `bind` does, whose levels are built by `build_level` instead
+    /// of `race_level`, e.g. a short track for testing. Clients still build theirs with
+    /// `race_level`, so this is only useful with clients that don't predict their players.
+    pub fn bind_with_levels(
+        address: &str,
+        build_level: fn(u64) -> Level,
+    ) -> std::io::Result<RaceServer> {
+        let socket = UdpSocket::bind(address)?;
+        // Don't block waiting for packets, so the server can keep ticking.
+        socket.set_nonblocking(true)?;
+        let seed = (date::now() * 1000.0) as u64;
+        Ok(RaceServer {
+            socket,
+            seed,
+            level: build_level(seed),
+            build_level,
+            race_time: 0.0,
+            clients: Vec::new(),
+            finished: 0,
+        })
+    }
+
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Runs the server forever: handling packets as they arrive, and `SERVER_TICK_RATE`
+    /// times a second moving the platforms and sending every client the race's state.
+    pub fn run(&mut self) -> std::io::Result<()> {
+        let tick = Duration::from_secs_f32(1.0 / SERVER_TICK_RATE);
+        let mut last_tick = Instant::now();
+        loop {
+            self.receive()?;
+            let elapsed = last_tick.elapsed();
+            if elapsed >= tick {
+                last_tick = Instant::now();
+                self.tick(elapsed.as_secs_f32());
+            }
+            // Sleep briefly so an idle server doesn't keep a CPU core busy.
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Handles every packet waiting on the socket.
+    pub fn receive(&mut self) -> std::io::Result<()> {
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            let (length, address) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(()),
+                // A client that went away can make the next receive fail on some systems.
+                Err(error) if error.kind() == ErrorKind::ConnectionReset => continue,
+                Err(error) => return Err(error),
+            };
+            let Some(packet) = Packet::decode(&buffer[..length]) else {
+                continue;
+            };
+            let client_index = self
+                .clients
+                .iter_mut()
+                .position(|client| client.address == address);
+            match (packet, client_index) {
+                (Packet::Join, None) => self.join(address),
+                // The welcome may have been lost, so send it again.
+                (Packet::Join, Some(index)) => {
+                    let player_id = self.clients[index].player_id;
+                    self.send(address, &self.welcome(player_id));
+                }
+                (
+                    Packet::Input {
+                        sequence,
+                        input,
+                        delta_time,
+                    },
+                    Some(index),
+                ) => self.apply_input(index, sequence, input, delta_time),
+                (Packet::Leave, Some(index)) => {
+                    self.clients.remove(index);
+                }
+                // Anything else is out of place and ignored.
+                _ => {}
+            }
+        }
+    }
+
+    /// Adds a new racer at the start of the level, if there is room.
+    /// Each racer gets the lowest id no current racer has, so ids are reused as racers leave
+    /// but two racers never share one.
+    pub fn join(&mut self, address: SocketAddr) {
+        if self.clients.len() >= MAX_RACE_PLAYERS {
+            return;
+        }
+        // A fresh race starts once everyone from the previous one has left.
+        if self.clients.is_empty() && self.finished > 0 {
+            self.seed = (date::now() * 1000.0) as u64;
+            self.level = (self.build_level)(self.seed);
+            self.race_time = 0.0;
+            self.finished = 0;
+        }
+        let Some(player_id) =
+            (0..=u8::MAX).find(|id| self.clients.iter().all(|client| client.player_id != *id))
+        else {
+            return;
+        };
+        self.clients.push(RaceClient {
+            address,
+            player_id,
+            player: Player::new(Controls::ARROWS, WHITE, 1, 0.0),
+            last_input: 0,
+            last_heard: Instant::now(),
+            time_budget: 0.0,
+            budget_filled: Instant::now(),
+            place: 0,
+        });
+        self.send(address, &self.welcome(player_id));
+    }
+
+    /// The welcome packet for the racer with id `player_id`.
+    pub fn welcome(&self, player_id: u8) -> Packet {
+        Packet::Welcome {
+            player_id,
+            seed: self.seed,
+        }
+    }
+
+    /// Moves a racer by one frame of their input. Inputs arriving late or twice are ignored,
+    /// and frame times are capped so a client can't move faster by claiming long frames.
+    /// Frame times that aren't a number (NaN or infinity) are ignored too, since they would
+    /// leave the racer's position broken for good.
+    /// Each client may only move its racer by as much time as has really passed: frames that
+    /// go over the client's time budget are shortened, or dropped once it is used up.
+    pub fn apply_input(
+        &mut self,
+        index: usize,
+        sequence: u32,
+        input: PlayerInput,
+        delta_time: f32,
+    ) {
+        let client = &mut self.clients[index];
+        client.last_heard = Instant::now();
+        if sequence <= client.last_input || client.place != 0 || !delta_time.is_finite() {
+            return;
+        }
+        client.last_input = sequence;
+
+        // Fill the budget with the time passed since it was last filled. The cap leaves room
+        // for inputs that arrive in bunches, without letting a client save up a head start.
+        let now = Instant::now();
+        let passed = now.duration_since(client.budget_filled).as_secs_f32();
+        client.time_budget = (client.time_budget + passed).min(MAX_INPUT_BUDGET);
+        client.budget_filled = now;
+        let delta_time = delta_time
+            .clamp(0.0, MAX_INPUT_DELTA)
+            .min(client.time_budget);
+        if delta_time <= 0.0 {
+            return;
+        }
+        client.time_budget -= delta_time;
+
+        let player = &mut client.player;
+        player.apply_input(input);
+        player.step(&self.level, delta_time);
+        // Falling into a pit or touching a spike sends the racer back to the start.
+        if player.fell_out(&self.level) || player.touches_spike(&self.level) {
+            player.respawn(PLAYER_START_POS);
+        } else if player.reached_house(&self.level) {
+            self.finished += 1;
+            client.place = self.finished;
+        }
+    }
+
+    /// Moves the platforms, forgets clients that went quiet, and sends everyone the race's state.
+    pub fn tick(&mut self, delta_time: f32) {
+        let standing_on: Vec<Entity> = self
+            .clients
+            .iter()
+            .filter_map(|client| client.player.standing_on)
+            .collect();
+        platform_system(&mut self.level.world, &standing_on, delta_time);
+        self.race_time += delta_time as f64;
+
+        let timeout = Duration::from_secs_f32(CLIENT_TIMEOUT);
+        self.clients
+            .retain(|client| client.last_heard.elapsed() < timeout);
+
+        let racers: Vec<RacerState> = self
+            .clients
+            .iter()
+            .map(|client| RacerState {
+                player_id: client.player_id,
+                position: client.player.body.entity.rect.point(),
+                velocity: client.player.body.velocity,
+                place: client.place,
+            })
+            .collect();
+        for client in &self.clients {
+            let state = Packet::State {
+                last_input: client.last_input,
+                race_time: self.race_time,
+                racers: racers.clone(),
+            };
+            self.send(client.address, &state);
+        }
+    }
+
+    /// Sends a packet to a client. UDP makes no promises anyway, so failures are ignored.
+    pub fn send(&self, address: SocketAddr, packet: &Packet) {
+        let _ = self.socket.send_to(&packet.encode(), address);
+    }
+}
+
+/// A request to join a race server that hasn't been answered yet. Checking on it never
+/// blocks, so the game can keep showing a "Connecting..." screen in the meantime.
+pub struct JoiningRace {
+    socket: UdpSocket,
+    /// When the join request was first sent, to give up after `CONNECT_TIMEOUT` seconds.
+    started: Instant,
+    /// When the join request was last sent. The request or its answer can be lost,
+    /// so it is sent `CONNECT_ATTEMPTS` times, spread over the timeout.
+    last_sent: Instant,
+}
+
+impl JoiningRace {
+    /// Sends a request to join the race server at `address`, e.g. "127.0.0.1:7777".
+    pub fn start(address: &str) -> std::io::Result<JoiningRace> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(address)?;
+        socket.set_nonblocking(true)?;
+        socket.send(&Packet::Join.encode())?;
+        let now = Instant::now();
+        Ok(JoiningRace {
+            socket,
+            started: now,
+            last_sent: now,
+        })
+    }
+
+    /// Checks whether the server has answered, asking again if it has been a while.
+    /// Returns the connection once the server lets us in, `None` while still waiting,
+    /// and an error if the server can't be reached or takes longer than `CONNECT_TIMEOUT`.
+    pub fn poll(&mut self) -> std::io::Result<Option<RaceConnection>> {
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            let length = match self.socket.recv(&mut buffer) {
+                Ok(length) => length,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => return Err(error),
+            };
+            if let Some(Packet::Welcome { player_id, seed }) = Packet::decode(&buffer[..length]) {
+                return Ok(Some(RaceConnection {
+                    socket: self.socket.try_clone()?,
+                    player_id,
+                    seed,
+                    next_sequence: 1,
+                    pending_inputs: VecDeque::new(),
+                }));
+            }
+        }
+
+        if self.started.elapsed().as_secs_f32() >= CONNECT_TIMEOUT {
+            return Err(std::io::Error::new(
+                ErrorKind::TimedOut,
+                "the race server did not answer",
+            ));
+        }
+        if self.last_sent.elapsed().as_secs_f32() >= CONNECT_TIMEOUT / CONNECT_ATTEMPTS as f32 {
+            self.socket.send(&Packet::Join.encode())?;
+            self.last_sent = Instant::now();
+        }
+        Ok(None)
+    }
+}
+
+/// A game's connection to a race server. The local player is predicted: inputs are applied
+/// straight away, and kept until the server confirms them, so they can be replayed on top of
+/// each state the server sends.
+pub struct RaceConnection {
+    socket: UdpSocket,
+    /// The id the server gave this player.
+    pub player_id: u8,
+    /// The seed of the level being raced.
+    pub seed: u64,
+    /// The sequence number of the next input to send.
+    next_sequence: u32,
+    /// Inputs sent but not yet confirmed by the server, with their frame times.
+    pending_inputs: VecDeque<(u32, PlayerInput, f32)>,
+}
+
+impl RaceConnection {
+    /// Joins the race server at `address`, waiting up to `CONNECT_TIMEOUT` seconds for it to
+    /// answer. This blocks until then, so the game uses `JoiningRace` to keep drawing while it
+    /// waits; this is for tools and tests.
+    pub fn connect(address: &str) -> std::io::Result<RaceConnection> {
+        let mut joining = JoiningRace::start(address)?;
+        loop {
+            if let Some(connection) = joining.poll()? {
+                return Ok(connection);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Sends one frame of input to the server, and remembers it until the server confirms it.
+    pub fn send_input(&mut self, input: PlayerInput, delta_time: f32) -> std::io::Result<()> {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.pending_inputs.push_back((sequence, input, delta_time));
+        let packet = Packet::Input {
+            sequence,
+            input,
+            delta_time,
+        };
+        self.socket.send(&packet.encode())?;
+        Ok(())
+    }
+
+    /// Reads every packet waiting on the socket. Returns the server's race time and the racers
+    /// from the newest state received, if any, after dropping the inputs that state has confirmed.
+    pub fn receive(&mut self) -> std::io::Result<Option<(f64, Vec<RacerState>)>> {
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        let mut newest = None;
+        loop {
+            let length = match self.socket.recv(&mut buffer) {
+                Ok(length) => length,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => return Err(error),
+            };
+            if let Some(Packet::State {
+                last_input,
+                race_time,
+                racers,
+            }) = Packet::decode(&buffer[..length])
+            {
+                self.pending_inputs
+                    .retain(|(sequence, _, _)| *sequence > last_input);
+                newest = Some((race_time, racers));
+            }
+        }
+        Ok(newest)
+    }
+
+    /// Moves the local player to where the server says they are,
+    /// then replays the inputs the server hasn't applied yet.
+    pub fn reconcile(&self, player: &mut Player, server_state: &RacerState, level: &Level) {
+        player.body.entity.rect.x = server_state.position.x;
+        player.body.entity.rect.y = server_state.position.y;
+        player.body.velocity = server_state.velocity;
+        player.standing_on = None;
+        for (_, input, delta_time) in &self.pending_inputs {
+            player.apply_input(*input);
+            player.step(level, *delta_time);
+        }
+    }
+
+    /// Tells the server this player is leaving. It would notice eventually anyway.
+    pub fn leave(&self) {
+        let _ = self.socket.send(&Packet::Leave.encode());
+    }
+}
+
+/// Runs a headless race server on `address` until it fails. Used by the `race_server` binary.
+pub fn run_race_server(address: &str) -> std::io::Result<()> {
+    let mut server = RaceServer::bind(address)?;
+    println!("Race server listening on {}", server.local_addr()?);
+    server.run()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::platform::platforms;
+
+    /// One packet of every kind, with values in every field.
+    fn every_packet() -> Vec<Packet> {
+        vec![
+            Packet::Join,
+            Packet::Welcome {
+                player_id: 3,
+                seed: 0x0123_4567_89ab_cdef,
+            },
+            Packet::Input {
+                sequence: 42,
+                input: PlayerInput {
+                    left: true,
+                    right: false,
+                    jump: true,
+                },
+                delta_time: 1.0 / 60.0,
+            },
+            Packet::State {
+                last_input: 41,
+                race_time: 12.5,
+                racers: vec![
+                    RacerState {
+                        player_id: 0,
+                        position: Vec2::new(243.0, 350.0),
+                        velocity: Vec2::new(-300.0, 12.25),
+                        place: 0,
+                    },
+                    RacerState {
+                        player_id: 3,
+                        position: Vec2::new(2900.5, 400.0),
+                        velocity: Vec2::ZERO,
+                        place: 1,
+                    },
+                ],
+            },
+            Packet::State {
+                last_input: 0,
+                race_time: 0.0,
+                racers: Vec::new(),
+            },
+            Packet::Leave,
+        ]
+    }
+
+    #[test]
+    fn packets_survive_encoding() {
+        for packet in every_packet() {
+            assert_eq!(Packet::decode(&packet.encode()), Some(packet));
+        }
+    }
+
+    #[test]
+    fn every_button_combination_survives_encoding() {
+        for buttons in 0..8 {
+            let packet = Packet::Input {
+                sequence: 1,
+                input: PlayerInput {
+                    left: buttons & 1 != 0,
+                    right: buttons & 2 != 0,
+                    jump: buttons & 4 != 0,
+                },
+                delta_time: 0.02,
+            };
+            assert_eq!(Packet::decode(&packet.encode()), Some(packet));
+        }
+    }
+
+    #[test]
+    fn truncated_packets_are_rejected() {
+        for packet in every_packet() {
+            let bytes = packet.encode();
+            for length in 0..bytes.len() {
+                assert_eq!(
+                    Packet::decode(&bytes[..length]),
+                    None,
+                    "{packet:?} cut to {length} bytes"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        assert_eq!(Packet::decode(&[]), None);
+        // Tags that no packet uses.
+        for tag in 5..=u8::MAX {
+            assert_eq!(Packet::decode(&[tag, 0, 0, 0, 0, 0, 0, 0, 0, 0]), None);
+        }
+        // A state claiming more racers than it holds.
+        let mut bytes = every_packet()[3].encode();
+        bytes[13] = 200;
+        assert_eq!(Packet::decode(&bytes), None);
+    }
+
+    #[test]
+    fn race_levels_have_no_crumbling_platforms() {
+        for seed in 0..20 {
+            let level = race_level(seed);
+            assert!(!platforms(&level.world)
+                .any(|(_, _, platform)| matches!(platform.kind, PlatformKind::Crumbling(_))));
+        }
+    }
+
+    #[test]
+    fn racers_move_no_faster_than_real_time() {
+        let mut server = RaceServer::bind("127.0.0.1:0").unwrap();
+        server.join("127.0.0.1:9".parse().unwrap());
+        let start_x = server.clients[0].player.body.entity.rect.x;
+        let started = Instant::now();
+        // Ten seconds' worth of the longest frames, sent as fast as possible.
+        let input = PlayerInput {
+            right: true,
+            ..PlayerInput::default()
+        };
+        for sequence in 1..=200 {
+            server.apply_input(0, sequence, input, MAX_INPUT_DELTA);
+        }
+        let seconds = started.elapsed().as_secs_f32();
+        let moved = server.clients[0].player.body.entity.rect.x - start_x;
+        assert!(
+            moved <= PLAYER_MOVEMENT_SPEED * (seconds + MAX_INPUT_DELTA),
+            "moved {moved} in {seconds} seconds"
+        );
+    }
+
+    #[test]
+    fn numbers_that_are_not_numbers_are_rejected() {
+        for bad in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let input = Packet::Input {
+                sequence: 1,
+                input: PlayerInput::default(),
+                delta_time: bad,
+            };
+            assert_eq!(Packet::decode(&input.encode()), None);
+            let state = Packet::State {
+                last_input: 1,
+                race_time: 1.0,
+                racers: vec![RacerState {
+                    player_id: 0,
+                    position: Vec2::new(bad, 0.0),
+                    velocity: Vec2::ZERO,
+                    place: 0,
+                }],
+            };
+            assert_eq!(Packet::decode(&state.encode()), None);
+        }
+        let state = Packet::State {
+            last_input: 1,
+            race_time: f64::NAN,
+            racers: Vec::new(),
+        };
+        assert_eq!(Packet::decode(&state.encode()), None);
+    }
+}
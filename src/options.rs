@@ -0,0 +1,227 @@
+//! The options a run is played with: mode, players and difficulty.
+
+use crate::constants::*;
+use crate::locale::Strings;
+use macroquad::prelude::*;
+
+/// All the values that change between difficulty levels.
+#[derive(Clone, Copy)]
+pub struct DifficultySettings {
+    /// How many chickens each level has.
+    pub chicken_count: u32,
+    /// Multiplier for how fast chickens fly.
+    pub chicken_speed: f32,
+    /// Percentage of chickens that are the tougher variant.
+    pub tough_chicken_chance: i32,
+    /// Relative chances of each chicken behaviour (wander, patrol, swoop, flee, perch).
+    pub chicken_behaviour_weights: [u32; 5],
+    /// Relative chances of each floating platform kind (static, moving, crumbling, bouncy, timed).
+    pub platform_kind_weights: [u32; 5],
+    /// Percentage chance of a spike at each possible spot on the ground.
+    pub spike_chance: i32,
+    /// Percentage of static platforms that get an egg.
+    pub egg_chance: i32,
+    /// How many times the player can die before the game is over.
+    pub lives: u32,
+    /// How many eggs are needed to reach the "End" state at the house.
+    pub eggs_needed_for_house: u32,
+    /// How many eggs are needed to win at the house.
+    pub eggs_needed_for_win: u32,
+}
+
+impl DifficultySettings {
+    /// Fewer, slower chickens, fewer spikes, more eggs and more lives.
+    pub const EASY: DifficultySettings = DifficultySettings {
+        chicken_count: 10,
+        chicken_speed: 0.7,
+        tough_chicken_chance: 0,
+        chicken_behaviour_weights: [6, 6, 2, 4, 6],
+        platform_kind_weights: [14, 2, 1, 2, 1],
+        spike_chance: 10,
+        egg_chance: 45,
+        lives: 5,
+        eggs_needed_for_house: 2,
+        eggs_needed_for_win: 4,
+    };
+
+    /// The game as originally designed.
+    pub const NORMAL: DifficultySettings = DifficultySettings {
+        chicken_count: 20,
+        chicken_speed: 1.0,
+        tough_chicken_chance: TOUGH_CHICKEN_CHANCE,
+        chicken_behaviour_weights: CHICKEN_BEHAVIOUR_WEIGHTS,
+        platform_kind_weights: PLATFORM_KIND_WEIGHTS,
+        spike_chance: 20,
+        egg_chance: 30,
+        lives: 3,
+        eggs_needed_for_house: EGGS_NEEDED_FOR_HOUSE,
+        eggs_needed_for_win: EGGS_NEEDED_FOR_WIN,
+    };
+
+    /// Lots of fast, aggressive chickens, many spikes, few eggs and a single life.
+    pub const HARD: DifficultySettings = DifficultySettings {
+        chicken_count: 35,
+        chicken_speed: 1.3,
+        tough_chicken_chance: 50,
+        chicken_behaviour_weights: [2, 3, 8, 1, 4],
+        platform_kind_weights: [6, 4, 5, 2, 4],
+        spike_chance: 40,
+        egg_chance: 20,
+        lives: 1,
+        eggs_needed_for_house: 3,
+        eggs_needed_for_win: 8,
+    };
+
+    /// The settings the player can change on the start screen when using the Custom difficulty,
+    /// as (label, current value) pairs.
+    /// `strings`: The words for the labels, in the chosen language.
+    pub fn custom_fields(&self, strings: &Strings) -> [(&'static str, String); 6] {
+        let labels = strings.custom_fields;
+        [
+            (labels[0], self.chicken_count.to_string()),
+            (labels[1], format!("{:.1}x", self.chicken_speed)),
+            (labels[2], format!("{}%", self.spike_chance)),
+            (labels[3], format!("{}%", self.egg_chance)),
+            (labels[4], self.lives.to_string()),
+            (labels[5], self.eggs_needed_for_win.to_string()),
+        ]
+    }
+
+    /// Changes one of the custom fields (by its index in `custom_fields`) up or down one step.
+    /// `step`: `1` to increase the value, `-1` to decrease it.
+    pub fn adjust_custom_field(&mut self, field: usize, step: i32) {
+        match field {
+            0 => self.chicken_count = (self.chicken_count as i32 + step * 5).clamp(0, 60) as u32,
+            1 => self.chicken_speed = (self.chicken_speed + step as f32 * 0.1).clamp(0.5, 2.0),
+            2 => self.spike_chance = (self.spike_chance + step * 10).clamp(0, 100),
+            3 => self.egg_chance = (self.egg_chance + step * 10).clamp(10, 100),
+            4 => self.lives = (self.lives as i32 + step).clamp(1, 9) as u32,
+            _ => {
+                self.eggs_needed_for_win =
+                    (self.eggs_needed_for_win as i32 + step).clamp(1, 15) as u32;
+                // Reaching the house should never need more eggs than winning does.
+                self.eggs_needed_for_house =
+                    self.eggs_needed_for_house.min(self.eggs_needed_for_win);
+            }
+        }
+    }
+}
+
+/// The difficulty presets the player can choose from on the start screen.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    /// Uses settings the player picked themselves.
+    Custom,
+}
+
+impl Difficulty {
+    /// All difficulties, in the order they are shown on the start screen.
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Custom,
+    ];
+
+    /// The name saved in the high-score file. It stays the same in every language.
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Custom => "Custom",
+        }
+    }
+
+    /// The name shown on screen, in the language of `strings`.
+    pub fn label(self, strings: &Strings) -> &'static str {
+        strings.difficulties[self as usize]
+    }
+
+    /// Finds the difficulty with the given name, as saved in the high-score file.
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name() == name)
+    }
+}
+
+/// The ways the game can be played, chosen on the start screen.
+#[derive(Clone, Copy, PartialEq)]
+pub enum GameMode {
+    /// Reach the house at the end of a level with enough eggs.
+    Story,
+    /// Run as far as possible through a level that never ends.
+    Endless,
+    /// Reach the house as fast as possible, racing the clock for a medal.
+    TimeAttack,
+    /// Race other players online to reach the house first.
+    Race,
+}
+
+impl GameMode {
+    /// All modes, in the order they are cycled through on the start screen.
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Story,
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Race,
+    ];
+
+    /// The name saved in the high-score file. It stays the same in every language.
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Story => "Story",
+            GameMode::Endless => "Endless",
+            GameMode::TimeAttack => "TimeAttack",
+            GameMode::Race => "Race",
+        }
+    }
+
+    /// The name shown on screen, in the language of `strings`.
+    pub fn label(self, strings: &Strings) -> &'static str {
+        strings.modes[self as usize]
+    }
+
+    /// Finds the mode with the given name, as saved in the high-score file.
+    pub fn from_name(name: &str) -> Option<GameMode> {
+        GameMode::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+}
+
+/// How many people are playing and whether they work together or compete.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Players {
+    /// One player on the arrow keys.
+    Single,
+    /// Two players on one keyboard, sharing one egg goal at the house.
+    CoOp,
+    /// Two players on one keyboard, racing to be first to collect the eggs needed to win.
+    Versus,
+}
+
+impl Players {
+    /// All choices, in the order they are cycled through on the start screen.
+    pub const ALL: [Players; 3] = [Players::Single, Players::CoOp, Players::Versus];
+
+    /// The name shown on the start screen, in the language of `strings`.
+    pub fn label(self, strings: &Strings) -> &'static str {
+        strings.players[self as usize]
+    }
+}
+
+/// Everything chosen on the start screen that a run is played with.
+#[derive(Clone, Copy)]
+pub struct RunOptions {
+    /// The mode being played.
+    pub mode: GameMode,
+    /// Who is playing: one player, or two working together or competing.
+    pub players: Players,
+    /// The difficulty preset chosen, used to keep high scores apart.
+    pub difficulty: Difficulty,
+    /// The actual values used for the run (the preset's, or the player's own for Custom).
+    pub settings: DifficultySettings,
+}
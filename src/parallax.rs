@@ -0,0 +1,313 @@
+//! The scenery around the level, in parallax layers.
+//!
+//! Things far away seem to move slower than things close by when you walk past them. Each layer
+//! has a scroll factor saying how fast it moves compared with the level: the background barely
+//! moves, far clouds move a little, near clouds a bit more, and foreground decorations in front
+//! of the level move faster than it. Together they make the world look deep instead of flat.
+//!
+//! Every layer is a world of its own, drawn through a camera that moves `scroll_factor` times
+//! as far as the level's camera. Which layers a level has is part of the level (`Level::parallax`).
+
+use crate::assets::Assets;
+use crate::constants::*;
+use crate::display::Display;
+use crate::world::components::{Behaviour, Layer, Sprite, SpriteImage, Transform};
+use crate::world::systems::{draw_system, movement_system, repeat_system};
+use crate::world::World;
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+/// What a parallax layer is filled with.
+#[derive(Clone, Copy, Debug)]
+pub enum LayerFill {
+    /// Copies of an image side by side with no gaps, like the chocolate background.
+    Tiled {
+        /// The image to repeat.
+        image: SpriteImage,
+        /// The size each copy is drawn at.
+        size: Vec2,
+        /// How far above the bottom of the level the centre of the images is.
+        height: f32,
+        /// How many copies there are. Copies left far behind are moved ahead again.
+        count: usize,
+    },
+    /// Copies of an image spread out at random heights, each drifting at its own speed,
+    /// like clouds.
+    Scattered {
+        /// The image to spread out.
+        image: SpriteImage,
+        /// The size each copy is drawn at.
+        size: Vec2,
+        /// The lowest and highest heights above the bottom of the level for the copies' centres.
+        heights: (f32, f32),
+        /// The slowest and fastest horizontal speeds (pixels per second). Use (0.0, 0.0) for
+        /// copies that stay put.
+        speeds: (f32, f32),
+        /// How far apart the copies are, left to right.
+        spacing: f32,
+        /// How many copies there are. Copies left far behind are moved ahead again.
+        count: usize,
+        /// Tint color for the copies, e.g. to fade distant ones into the sky.
+        tint: Color,
+    },
+}
+
+/// One layer of scenery.
+#[derive(Clone, Copy, Debug)]
+pub struct ParallaxLayer {
+    /// What the layer is made of.
+    pub fill: LayerFill,
+    /// How far the layer moves compared with the level: 1.0 moves with the level, 0.0 never moves,
+    /// and more than 1.0 moves faster (for things in front of the level).
+    pub scroll_factor: f32,
+    /// How far down (in pixels) the whole layer is moved, e.g. to sink a layer behind the ground.
+    pub y_offset: f32,
+    /// Draws the layer in front of the level and players instead of behind them.
+    pub in_front: bool,
+}
+
+/// The background far away, with far and near clouds in front of it.
+const SKY: ParallaxLayer = ParallaxLayer {
+    fill: LayerFill::Tiled {
+        image: SpriteImage::Background,
+        size: BACKGROUND_SIZE,
+        height: 432.0,
+        count: 8,
+    },
+    scroll_factor: 0.2,
+    y_offset: 0.0,
+    in_front: false,
+};
+const FAR_CLOUDS: ParallaxLayer = ParallaxLayer {
+    fill: LayerFill::Scattered {
+        image: SpriteImage::Cloud,
+        size: Vec2::new(CLOUD_SIZE.x / 2.0, CLOUD_SIZE.y / 2.0),
+        heights: (400.0, 700.0),
+        speeds: (5.0, 15.0),
+        spacing: 400.0,
+        count: 30,
+        tint: FAR_CLOUD_TINT,
+    },
+    scroll_factor: 0.4,
+    y_offset: 0.0,
+    in_front: false,
+};
+const NEAR_CLOUDS: ParallaxLayer = ParallaxLayer {
+    fill: LayerFill::Scattered {
+        image: SpriteImage::Cloud,
+        size: CLOUD_SIZE,
+        heights: (268.0, 668.0),
+        speeds: (20.0, 60.0),
+        spacing: 500.0,
+        count: 24,
+        tint: WHITE,
+    },
+    scroll_factor: 0.7,
+    y_offset: 0.0,
+    in_front: false,
+};
+/// Big, faint clouds passing close to the camera, along the top of the screen.
+const FOREGROUND_CLOUDS: ParallaxLayer = ParallaxLayer {
+    fill: LayerFill::Scattered {
+        image: SpriteImage::Cloud,
+        size: Vec2::new(CLOUD_SIZE.x * 1.5, CLOUD_SIZE.y * 1.5),
+        heights: (720.0, 780.0),
+        speeds: (0.0, 0.0),
+        spacing: 2500.0,
+        count: 4,
+        tint: FOREGROUND_TINT,
+    },
+    scroll_factor: 1.4,
+    y_offset: 0.0,
+    in_front: true,
+};
+
+/// The layers of the story and time-attack levels.
+pub const STORY_LAYERS: [ParallaxLayer; 4] = [SKY, FAR_CLOUDS, NEAR_CLOUDS, FOREGROUND_CLOUDS];
+/// The layers of endless levels: a hazier sky, sunk a little, with more far clouds.
+pub const ENDLESS_LAYERS: [ParallaxLayer; 3] = [
+    ParallaxLayer {
+        y_offset: 150.0,
+        ..SKY
+    },
+    ParallaxLayer {
+        fill: LayerFill::Scattered {
+            image: SpriteImage::Cloud,
+            size: Vec2::new(CLOUD_SIZE.x / 2.0, CLOUD_SIZE.y / 2.0),
+            heights: (300.0, 750.0),
+            speeds: (5.0, 15.0),
+            spacing: 250.0,
+            count: 48,
+            tint: FAR_CLOUD_TINT,
+        },
+        ..FAR_CLOUDS
+    },
+    NEAR_CLOUDS,
+];
+/// The layers of online races, with nothing in front of the level to hide the other racers.
+pub const RACE_LAYERS: [ParallaxLayer; 3] = [SKY, FAR_CLOUDS, NEAR_CLOUDS];
+
+/// The scenery around the level: a world of entities for each parallax layer, so they can be
+/// moved and drawn by the same systems as everything in the level.
+pub struct Scenery {
+    /// Each layer, and the entities in it, from the back to the front.
+    layers: Vec<(ParallaxLayer, World)>,
+    /// The world y position of the bottom of the level. Layers stay lined up with it.
+    bottom: f32,
+}
+
+impl Scenery {
+    /// Fills each layer with its images along the start of the level.
+    /// `layers`: The level's parallax layers, from the back to the front.
+    /// `bottom`: The world y position of the bottom of the level.
+    pub fn new(layers: &[ParallaxLayer], bottom: f32) -> Scenery {
+        let layers = layers
+            .iter()
+            .map(|layer| (*layer, Scenery::fill_layer(layer, bottom)))
+            .collect();
+        Scenery { layers, bottom }
+    }
+
+    /// Creates the entities of one layer.
+    fn fill_layer(layer: &ParallaxLayer, bottom: f32) -> World {
+        let mut world = World::new();
+        match layer.fill {
+            LayerFill::Tiled {
+                image,
+                size,
+                height,
+                count,
+            } => {
+                // The images are placed side-by-side to create a long scrolling strip.
+                for i in 0..count {
+                    let x = -size.x + i as f32 * size.x;
+                    let entity = world.spawn();
+                    world.place(
+                        entity,
+                        Transform::new(Rect {
+                            x,
+                            y: bottom - height - size.y / 2.0, // Centre the image vertically
+                            w: size.x,
+                            h: size.y,
+                        }),
+                    );
+                    world.sprites.insert(entity, Sprite::new(image, Layer::Sky));
+                    // Once the players leave it far behind, move it past the last image.
+                    world.behaviours.insert(
+                        entity,
+                        Behaviour::Repeat {
+                            spacing: count as f32 * size.x,
+                        },
+                    );
+                }
+            }
+            LayerFill::Scattered {
+                image,
+                size,
+                heights,
+                speeds,
+                spacing,
+                count,
+                tint,
+            } => {
+                for i in 0..count {
+                    // Spread the copies out horizontally, at random heights.
+                    let x = -1024.0 + spacing * i as f32;
+                    let y = bottom - gen_range(heights.0, heights.1);
+                    let entity = world.spawn();
+                    world.place(
+                        entity,
+                        Transform::new(Rect {
+                            x: x - size.x / 2.0, // Centre the image
+                            y: y - size.y / 2.0, // Centre the image
+                            w: size.x,
+                            h: size.y,
+                        }),
+                    );
+                    // Give each copy a random horizontal speed (no vertical velocity).
+                    if speeds.1 > 0.0 {
+                        world
+                            .velocities
+                            .insert(entity, Vec2::new(gen_range(speeds.0, speeds.1), 0.0));
+                    }
+                    world.sprites.insert(
+                        entity,
+                        Sprite {
+                            tint,
+                            ..Sprite::new(image, Layer::Clouds)
+                        },
+                    );
+                    // Once the players leave it far behind, move it ahead of them again.
+                    world.behaviours.insert(
+                        entity,
+                        Behaviour::Repeat {
+                            spacing: count as f32 * spacing,
+                        },
+                    );
+                }
+            }
+        }
+        world
+    }
+
+    /// The area of a layer on screen when the level's camera shows `view`.
+    /// The layer's camera moves `scroll_factor` times as far as the level's camera, measured from
+    /// the start of the level (x=0) and the bottom of the level, so that a view resting on the
+    /// bottom of the level shows every layer at the height it was placed at.
+    fn layer_view(&self, layer: &ParallaxLayer, view: Rect) -> Rect {
+        let view_bottom = self.bottom + (view.bottom() - self.bottom) * layer.scroll_factor;
+        Rect::new(
+            view.x * layer.scroll_factor,
+            // Moving the camera up moves the layer down.
+            view_bottom - view.h - layer.y_offset,
+            view.w,
+            view.h,
+        )
+    }
+
+    /// Moves the clouds, and moves anything left far behind the players ahead of them again.
+    /// `far_behind_x`: Anything completely left of this x position (in the level) is moved ahead.
+    /// `reduced_motion`: Whether the clouds should drift slowly, for players bothered by motion.
+    pub fn update(&mut self, delta_time: f32, far_behind_x: f32, reduced_motion: bool) {
+        // With reduced motion the clouds drift along slowly, but still keep up with the level.
+        let drift = if reduced_motion {
+            REDUCED_MOTION_DRIFT
+        } else {
+            1.0
+        };
+        for (layer, world) in &mut self.layers {
+            movement_system(world, delta_time * drift);
+            // The same spot in the level is `scroll_factor` times as far along in this layer.
+            repeat_system(world, far_behind_x * layer.scroll_factor);
+        }
+    }
+
+    /// Draws the layers behind the level. Leaves the camera showing `view` afterwards.
+    /// `display`: The virtual screen the world is drawn on.
+    /// `view`: The area of the level on screen.
+    pub fn draw_behind(&self, assets: &Assets, display: &Display, view: Rect) {
+        self.draw_layers(assets, display, view, false);
+    }
+
+    /// Draws the layers in front of the level. Leaves the camera showing `view` afterwards.
+    /// `display`: The virtual screen the world is drawn on.
+    /// `view`: The area of the level on screen.
+    pub fn draw_in_front(&self, assets: &Assets, display: &Display, view: Rect) {
+        self.draw_layers(assets, display, view, true);
+    }
+
+    /// Draws each layer that is (or isn't) `in_front` through its own camera.
+    fn draw_layers(&self, assets: &Assets, display: &Display, view: Rect, in_front: bool) {
+        for (layer, world) in self
+            .layers
+            .iter()
+            .filter(|(layer, _)| layer.in_front == in_front)
+        {
+            let layer_view = self.layer_view(layer, view);
+            display.set_world_camera(layer_view);
+            draw_system(world, assets, layer_view);
+        }
+        // Put the level's camera back.
+        display.set_world_camera(view);
+    }
+}
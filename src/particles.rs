@@ -0,0 +1,183 @@
+//! Particle effects: lots of tiny, short-lived squares thrown out in a burst, like sparkles
+//! around a collected egg, dust under the player's feet or confetti when a run is won.
+//!
+//! Particles don't touch anything and nothing touches them, so unlike entities they are kept in a
+//! plain list, moved with a few lines of maths and forgotten once their time is up.
+
+use crate::constants::*;
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+use std::f32::consts::PI;
+
+/// How a burst of particles looks and moves. Every particle picks its own values from the ranges.
+#[derive(Clone, Copy, Debug)]
+pub struct Emitter {
+    /// How many particles one burst throws out.
+    pub count: usize,
+    /// The slowest and fastest speeds (pixels per second) particles start with.
+    pub speed: (f32, f32),
+    /// The direction particles are thrown in, as an angle in radians
+    /// (0 is right, and since y points down, -PI/2 is straight up).
+    pub direction: f32,
+    /// How far (in radians) either side of `direction` particles may go. PI throws them
+    /// every way.
+    pub spread: f32,
+    /// The shortest and longest times (in seconds) particles last.
+    pub lifetime: (f32, f32),
+    /// How quickly particles fall (pixels per second squared). Negative values make them rise.
+    pub gravity: f32,
+    /// The smallest and largest particle sizes (in pixels).
+    pub size: (f32, f32),
+    /// The fastest particles spin (radians per second), either way.
+    pub spin: f32,
+    /// The colors particles can have. Each particle picks one, then fades out over its lifetime.
+    pub colors: &'static [Color],
+}
+
+/// Sparkles bursting out of a collected egg.
+pub const EGG_SPARKLE: Emitter = Emitter {
+    count: 16,
+    speed: (60.0, 180.0),
+    direction: 0.0,
+    spread: PI,
+    lifetime: (0.3, 0.7),
+    gravity: -40.0,
+    size: (3.0, 6.0),
+    spin: 6.0,
+    colors: &EGG_SPARKLE_COLORS,
+};
+/// A puff of dust kicked up from the player's feet when jumping or landing.
+pub const DUST_PUFF: Emitter = Emitter {
+    count: 8,
+    speed: (30.0, 90.0),
+    direction: -PI / 2.0,
+    spread: PI / 2.0,
+    lifetime: (0.25, 0.5),
+    gravity: 60.0,
+    size: (4.0, 8.0),
+    spin: 0.0,
+    colors: &DUST_COLORS,
+};
+/// Feathers flying off a chicken that was stomped on, drifting slowly down.
+pub const FEATHERS: Emitter = Emitter {
+    count: 10,
+    speed: (40.0, 140.0),
+    direction: -PI / 2.0,
+    spread: PI * 0.8,
+    lifetime: (0.6, 1.2),
+    gravity: 120.0,
+    size: (5.0, 9.0),
+    spin: 8.0,
+    colors: &FEATHER_COLORS,
+};
+/// Confetti thrown up from the bottom of the screen when a run is won.
+pub const CONFETTI: Emitter = Emitter {
+    count: 40,
+    speed: (350.0, 650.0),
+    direction: -PI / 2.0,
+    spread: PI / 6.0,
+    lifetime: (1.5, 2.5),
+    gravity: 400.0,
+    size: (6.0, 10.0),
+    spin: 10.0,
+    colors: &CONFETTI_COLORS,
+};
+
+/// One particle in flight.
+struct Particle {
+    /// Where the centre of the particle is.
+    position: Vec2,
+    /// How fast it is moving (pixels per second).
+    velocity: Vec2,
+    /// How quickly it falls (pixels per second squared).
+    gravity: f32,
+    /// How far it has turned (radians), and how fast it turns (radians per second).
+    rotation: f32,
+    spin: f32,
+    /// Its width and height (in pixels).
+    size: f32,
+    /// Its color when it appeared. It fades out from this.
+    color: Color,
+    /// How long (in seconds) it has been around, and how long it lasts.
+    age: f32,
+    lifetime: f32,
+}
+
+/// Every particle in flight.
+pub struct Particles {
+    particles: Vec<Particle>,
+}
+
+impl Particles {
+    /// Creates an empty set of particles.
+    pub fn new() -> Particles {
+        Particles {
+            particles: Vec::new(),
+        }
+    }
+
+    /// Throws out one burst of particles.
+    /// `emitter`: How the particles look and move.
+    /// `position`: Where they start.
+    pub fn burst(&mut self, emitter: &Emitter, position: Vec2) {
+        // Past the limit, new bursts are skipped instead of slowing the game down.
+        let room = MAX_PARTICLES.saturating_sub(self.particles.len());
+        for _ in 0..emitter.count.min(room) {
+            let angle = emitter.direction + gen_range(-emitter.spread, emitter.spread);
+            let speed = gen_range(emitter.speed.0, emitter.speed.1);
+            self.particles.push(Particle {
+                position,
+                velocity: Vec2::from_angle(angle) * speed,
+                gravity: emitter.gravity,
+                rotation: gen_range(0.0, PI),
+                spin: gen_range(-emitter.spin, emitter.spin),
+                size: gen_range(emitter.size.0, emitter.size.1),
+                color: emitter.colors[gen_range(0, emitter.colors.len())],
+                age: 0.0,
+                lifetime: gen_range(emitter.lifetime.0, emitter.lifetime.1),
+            });
+        }
+    }
+
+    /// Moves every particle, and forgets those whose time is up.
+    /// `delta_time`: The time in seconds since the last frame update.
+    pub fn update(&mut self, delta_time: f32) {
+        for particle in &mut self.particles {
+            particle.velocity.y += particle.gravity * delta_time;
+            particle.position += particle.velocity * delta_time;
+            particle.rotation += particle.spin * delta_time;
+            particle.age += delta_time;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    /// Draws every particle, fading each one out as it gets older.
+    /// Particles are drawn with whatever camera is set, so they can be in the world or on screen.
+    pub fn draw(&self) {
+        for particle in &self.particles {
+            let fade = 1.0 - particle.age / particle.lifetime;
+            draw_rectangle_ex(
+                particle.position.x,
+                particle.position.y,
+                particle.size,
+                particle.size,
+                DrawRectangleParams {
+                    // Turn around the particle's centre.
+                    offset: Vec2::new(0.5, 0.5),
+                    rotation: particle.rotation,
+                    color: Color {
+                        a: particle.color.a * fade,
+                        ..particle.color
+                    },
+                },
+            );
+        }
+    }
+}
+
+impl Default for Particles {
+    fn default() -> Self {
+        Particles::new()
+    }
+}
@@ -0,0 +1,43 @@
+//! The bodies the players are made of: rectangles that can be collided with, and ones that move.
+//! Everything else in a level is an entity in a `World`.
+
+use crate::constants::*;
+use macroquad::prelude::*;
+
+/// Represents a basic game object with a position and size (a rectangle).
+pub struct GameEntity {
+    /// The rectangle defining the entity's position (x, y) and dimensions (w, h).
+    pub rect: Rect,
+}
+
+impl GameEntity {
+    /// Calculates the collision bounding box, slightly smaller than the visual rectangle.
+    /// This uses `COLLISION_MARGIN` to prevent overly sensitive collisions.
+    pub fn get_collision_bounds(&self) -> Rect {
+        Rect {
+            x: self.rect.x + COLLISION_MARGIN,       // Move right edge inwards
+            y: self.rect.y + COLLISION_MARGIN,       // Move top edge downwards
+            w: self.rect.w - COLLISION_MARGIN * 2.0, // Reduce width
+            h: self.rect.h - COLLISION_MARGIN * 2.0, // Reduce height
+        }
+    }
+}
+
+/// Represents a game entity that can move.
+/// Contains a `GameEntity` for position/size and a `velocity` vector.
+pub struct MovingGameEntity {
+    /// The underlying entity with position and size.
+    pub entity: GameEntity,
+    /// The speed and direction of movement (pixels per second).
+    pub velocity: Vec2,
+}
+
+impl MovingGameEntity {
+    /// Updates the entity's position based on its velocity and the time elapsed since the last frame.
+    /// `delta_time`: The time in seconds since the last frame update.
+    pub fn apply_velocity(&mut self, delta_time: f32) {
+        // Update position: position = position + velocity * time
+        self.entity.rect.x += self.velocity.x * delta_time;
+        self.entity.rect.y += self.velocity.y * delta_time;
+    }
+}
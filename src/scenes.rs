@@ -0,0 +1,245 @@
+//! The scene stack: the start screen, the game, pause menus and so on are all scenes,
+//! stacked on top of each other. Only the top scene is updated each frame, so pushing a pause
+//! menu freezes the game underneath it, and popping the menu carries on where it left off.
+//! Changes between scenes can cut straight over, fade through black, or wipe across the screen.
+
+use crate::assets::Assets;
+use crate::constants::*;
+use crate::display::Display;
+#[cfg(feature = "hot-reload")]
+use crate::hot_reload::HotReloader;
+use crate::scores::HighScores;
+use crate::settings::Settings;
+use macroquad::prelude::*;
+
+/// Everything the scenes share, which lives for as long as the game runs.
+pub struct SceneContext {
+    /// The loaded game assets.
+    pub assets: Assets,
+    /// The high-score table, recorded into at the end of each run.
+    pub high_scores: HighScores,
+    /// The player's settings, like the language.
+    pub settings: Settings,
+    /// The race server to join when the Race mode is chosen.
+    pub server_address: String,
+    /// The virtual screen everything is drawn on.
+    pub display: Display,
+    /// Reloads assets whose files change, while developing.
+    #[cfg(feature = "hot-reload")]
+    pub hot_reloader: HotReloader,
+}
+
+/// One screen of the game. Scenes update themselves once a frame while they are on top of
+/// the stack, and tell the stack when to move on by returning a `SceneCommand`.
+pub trait Scene {
+    /// Called when the scene is put on the stack, before it is first updated or drawn.
+    /// `context`: The state shared between scenes.
+    fn enter(&mut self, _context: &mut SceneContext) {}
+
+    /// Called when the scene is taken off the stack, after it was last updated.
+    /// `context`: The state shared between scenes.
+    fn exit(&mut self, _context: &mut SceneContext) {}
+
+    /// Advances the scene by one frame, and returns what the stack should do next.
+    /// Only called while the scene is on top of the stack and no transition is playing.
+    /// `context`: The state shared between scenes.
+    /// `delta_time`: The time elapsed since the last frame (in seconds).
+    fn update(&mut self, context: &mut SceneContext, delta_time: f32) -> SceneCommand;
+
+    /// Draws the scene onto the virtual screen. Scenes may move the camera, but should switch
+    /// back to the screen camera (`Display::set_screen_camera`) before returning, so whatever is
+    /// drawn on top of them lines up with the screen.
+    /// `context`: The state shared between scenes.
+    fn draw(&self, context: &SceneContext);
+
+    /// Whether the scenes underneath show through this one (e.g., a pause menu over the game).
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// What the scene stack should do after a scene's update.
+pub enum SceneCommand {
+    /// Keep running the current scene.
+    Stay,
+    /// Put a new scene on top of the current one, which stays on the stack, frozen.
+    Push(Box<dyn Scene>, Transition),
+    /// Take the current scene off the stack, returning to the one underneath.
+    /// Popping the last scene ends the game.
+    Pop(Transition),
+    /// Swap the current scene for a new one.
+    Replace(Box<dyn Scene>, Transition),
+}
+
+/// How the screen changes from one scene to the next.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Transition {
+    /// Switch straight to the next scene.
+    Cut,
+    /// Fade out to black, then fade back in on the next scene.
+    Fade,
+    /// Sweep a black curtain across the screen from left to right, and pull it away
+    /// to the right to reveal the next scene.
+    Wipe,
+}
+
+/// A fade or wipe in progress, carrying the command it applies once the screen is covered.
+struct ActiveTransition {
+    kind: Transition,
+    elapsed: f32,
+    command: Option<SceneCommand>,
+}
+
+/// The stack of scenes, from the bottom (first) to the top (last), and any transition playing.
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    transition: Option<ActiveTransition>,
+}
+
+impl SceneStack {
+    /// Creates a stack holding only `first`, which is entered straight away.
+    /// `first`: The scene to start with.
+    /// `context`: The state shared between scenes.
+    pub fn new(mut first: Box<dyn Scene>, context: &mut SceneContext) -> SceneStack {
+        first.enter(context);
+        SceneStack {
+            scenes: vec![first],
+            transition: None,
+        }
+    }
+
+    /// Runs the scenes, one frame at a time, until the last scene is popped off the stack.
+    /// `context`: The state shared between scenes.
+    pub async fn run(&mut self, context: &mut SceneContext) {
+        while !self.scenes.is_empty() {
+            // Wait for the next frame before updating and drawing again.
+            next_frame().await;
+            let delta_time = get_frame_time();
+            #[cfg(feature = "hot-reload")]
+            context.hot_reloader.update(&mut context.assets).await;
+
+            match &mut self.transition {
+                // While a transition plays, the scenes are frozen. Once the screen is fully
+                // covered, the change happens out of sight.
+                Some(transition) => {
+                    transition.elapsed += delta_time;
+                    let covered = transition.elapsed >= TRANSITION_TIME / 2.0;
+                    let finished = transition.elapsed >= TRANSITION_TIME;
+                    if let Some(command) = covered.then(|| transition.command.take()).flatten() {
+                        self.apply(command, context);
+                    }
+                    if finished {
+                        self.transition = None;
+                    }
+                }
+                // Otherwise, the top scene runs and decides what happens next.
+                None => {
+                    if let Some(top) = self.scenes.last_mut() {
+                        let command = top.update(context, delta_time);
+                        self.start(command, context);
+                    }
+                }
+            }
+
+            // Draw onto the virtual screen, then scale it up to fill the window.
+            context.display.begin();
+            self.draw(context);
+            context.display.present();
+        }
+    }
+
+    /// Starts carrying out a scene's command: straight away for a cut, or once a fade or wipe
+    /// has covered the screen.
+    fn start(&mut self, command: SceneCommand, context: &mut SceneContext) {
+        let kind = match &command {
+            SceneCommand::Stay => return,
+            SceneCommand::Push(_, kind)
+            | SceneCommand::Pop(kind)
+            | SceneCommand::Replace(_, kind) => *kind,
+        };
+        if kind == Transition::Cut {
+            self.apply(command, context);
+        } else {
+            self.transition = Some(ActiveTransition {
+                kind,
+                elapsed: 0.0,
+                command: Some(command),
+            });
+        }
+    }
+
+    /// Changes the stack as the command says, calling the exit and enter hooks.
+    fn apply(&mut self, command: SceneCommand, context: &mut SceneContext) {
+        match command {
+            SceneCommand::Stay => {}
+            SceneCommand::Push(mut scene, _) => {
+                scene.enter(context);
+                self.scenes.push(scene);
+            }
+            SceneCommand::Pop(_) => {
+                if let Some(mut scene) = self.scenes.pop() {
+                    scene.exit(context);
+                }
+            }
+            SceneCommand::Replace(mut scene, _) => {
+                if let Some(mut old) = self.scenes.pop() {
+                    old.exit(context);
+                }
+                scene.enter(context);
+                self.scenes.push(scene);
+            }
+        }
+    }
+
+    /// Draws the top scene, any scenes showing through it, and the transition on top.
+    fn draw(&self, context: &SceneContext) {
+        // Start drawing from the highest scene that covers the whole screen.
+        let first_visible = self
+            .scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        for scene in &self.scenes[first_visible..] {
+            scene.draw(context);
+        }
+
+        if let Some(transition) = &self.transition {
+            context.display.set_screen_camera();
+            draw_transition(transition);
+        }
+    }
+}
+
+/// Draws a fade or wipe part-way through.
+/// `transition`: The transition playing, and how far through it is.
+fn draw_transition(transition: &ActiveTransition) {
+    let half_time = TRANSITION_TIME / 2.0;
+    let covering = transition.elapsed < half_time;
+    // How much of the screen is covered: rising from 0 to 1, then falling back to 0.
+    let amount = if covering {
+        transition.elapsed / half_time
+    } else {
+        (TRANSITION_TIME - transition.elapsed) / half_time
+    }
+    .clamp(0.0, 1.0);
+
+    match transition.kind {
+        Transition::Cut => {}
+        Transition::Fade => draw_rectangle(
+            0.0,
+            0.0,
+            VIRTUAL_WIDTH,
+            VIRTUAL_HEIGHT,
+            Color {
+                a: amount,
+                ..TRANSITION_COLOR
+            },
+        ),
+        Transition::Wipe => {
+            // The curtain grows from the left edge, then shrinks towards the right edge.
+            let width = amount * VIRTUAL_WIDTH;
+            let x = if covering { 0.0 } else { VIRTUAL_WIDTH - width };
+            draw_rectangle(x, 0.0, width, VIRTUAL_HEIGHT, TRANSITION_COLOR);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Keeping score: the high-score table, run times and time-attack medals.
+
+use crate::constants::*;
+use crate::locale::Strings;
+use crate::options::{Difficulty, GameMode};
+use macroquad::prelude::*;
+
+/// The medals a time-attack run can earn, best first.
+#[derive(Clone, Copy)]
+pub enum Medal {
+    Gold,
+    Silver,
+    Bronze,
+}
+
+impl Medal {
+    /// All medals, in the same order as a level's `medal_times`.
+    pub const ALL: [Medal; 3] = [Medal::Gold, Medal::Silver, Medal::Bronze];
+
+    /// The best medal earned by finishing in `time` seconds, if any.
+    /// `medal_times`: The times needed for gold, silver and bronze.
+    pub fn for_time(time: f32, medal_times: [f32; 3]) -> Option<Medal> {
+        Medal::ALL
+            .into_iter()
+            .zip(medal_times)
+            .find(|(_, medal_time)| time <= *medal_time)
+            .map(|(medal, _)| medal)
+    }
+
+    /// The name shown on screen, in the language of `strings`.
+    pub fn label(self, strings: &Strings) -> &'static str {
+        strings.medals[self as usize]
+    }
+
+    /// The colour the medal is drawn in.
+    pub fn color(self) -> Color {
+        MEDAL_COLORS[self as usize]
+    }
+}
+
+/// The timing of a finished time-attack run.
+pub struct RunTime {
+    /// The total time (in seconds) from the start to reaching the house.
+    pub time: f32,
+    /// The time (in seconds since the start) at which each checkpoint was passed.
+    pub splits: Vec<f32>,
+    /// The medal earned, if the run was fast enough.
+    pub medal: Option<Medal>,
+}
+
+/// Formats a time in seconds as minutes, seconds and hundredths, e.g. "1:05.42".
+pub fn format_time(seconds: f32) -> String {
+    let hundredths = (seconds * 100.0).round() as u32;
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
+
+/// A single finished run in the high-score table.
+pub struct ScoreEntry {
+    /// The mode the run was played in.
+    pub mode: GameMode,
+    /// The difficulty the run was played on.
+    pub difficulty: Difficulty,
+    /// The final score of the run.
+    pub score: u32,
+    /// How long (in seconds) a time-attack run took to reach the house, if it did.
+    pub time: Option<f32>,
+}
+
+/// The best scores (and fastest times) for each mode and difficulty,
+/// saved to `HIGH_SCORES_FILE` between games.
+pub struct HighScores {
+    pub entries: Vec<ScoreEntry>,
+}
+
+impl HighScores {
+    /// Loads the high-score table from disk. Starts empty if the file is missing or unreadable
+    /// (for example when running in a web browser, where there is no file system).
+    pub fn load() -> HighScores {
+        let contents = std::fs::read_to_string(HIGH_SCORES_FILE).unwrap_or_default();
+        // Each line looks like "Story Normal 12", with the time added for finished
+        // time-attack runs, e.g. "TimeAttack Normal 12 45.67". Lines that don't parse are skipped.
+        let entries = contents
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                Some(ScoreEntry {
+                    mode: GameMode::from_name(parts.next()?)?,
+                    difficulty: Difficulty::from_name(parts.next()?)?,
+                    score: parts.next()?.parse().ok()?,
+                    time: parts.next().and_then(|time| time.parse().ok()),
+                })
+            })
+            .collect();
+        HighScores { entries }
+    }
+
+    /// Adds a finished run to the table and saves it, keeping only the best
+    /// `HIGH_SCORES_PER_DIFFICULTY` runs for each mode and difficulty.
+    /// Runs with a time rank by fastest time first, then everything else by highest score.
+    pub fn record(
+        &mut self,
+        mode: GameMode,
+        difficulty: Difficulty,
+        score: u32,
+        time: Option<f32>,
+    ) {
+        self.entries.push(ScoreEntry {
+            mode,
+            difficulty,
+            score,
+            time,
+        });
+        // Sort best first, then drop everything past the limit for each mode and difficulty.
+        self.entries.sort_by(|a, b| {
+            let a_time = a.time.unwrap_or(f32::INFINITY);
+            let b_time = b.time.unwrap_or(f32::INFINITY);
+            a_time.total_cmp(&b_time).then(b.score.cmp(&a.score))
+        });
+        let mut kept_per_table = [[0; Difficulty::ALL.len()]; GameMode::ALL.len()];
+        self.entries.retain(|entry| {
+            let kept = &mut kept_per_table[entry.mode as usize][entry.difficulty as usize];
+            *kept += 1;
+            *kept <= HIGH_SCORES_PER_DIFFICULTY
+        });
+
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let time = entry.time.map(|time| format!(" {time:.2}"));
+                format!(
+                    "{} {} {}{}\n",
+                    entry.mode.name(),
+                    entry.difficulty.name(),
+                    entry.score,
+                    time.unwrap_or_default()
+                )
+            })
+            .collect();
+        // Failing to save is not worth interrupting the game for.
+        let _ = std::fs::write(HIGH_SCORES_FILE, contents);
+    }
+
+    /// The best runs for one mode and difficulty, best first.
+    pub fn top(
+        &self,
+        mode: GameMode,
+        difficulty: Difficulty,
+    ) -> impl Iterator<Item = &ScoreEntry> + '_ {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.mode == mode && entry.difficulty == difficulty)
+    }
+}
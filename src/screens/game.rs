@@ -0,0 +1,447 @@
+//! The main game screen, where the levels are played.
+
+use crate::audio::play_effect;
+use crate::camera::CameraController;
+use crate::captions::{Captions, Cue};
+use crate::constants::*;
+use crate::entities::chicken::chicken_system;
+use crate::entities::platform::platform_system;
+use crate::entities::player::{Controls, Player};
+use crate::level::Level;
+use crate::locale::fill;
+use crate::options::{GameMode, Players, RunOptions};
+use crate::parallax::Scenery;
+use crate::particles::Particles;
+use crate::scenes::{Scene, SceneCommand, SceneContext, Transition};
+use crate::scores::{format_time, Medal, RunTime};
+use crate::screens::game_over::GameOverScreen;
+use crate::screens::pause::PauseScreen;
+use crate::screens::{camera_focus, draw_level, GameOverReason};
+use crate::text::{draw_text_line, HUD_TEXT};
+use crate::world::systems::{hazard_system, movement_system};
+use crate::world::Entity;
+use macroquad::miniquad::date;
+use macroquad::prelude::*;
+
+/// The main game, handling player input, physics, collisions, and drawing.
+/// Moves on to the game over screen when the game ends, and pauses when Escape is pressed.
+pub struct GameScreen {
+    options: RunOptions,
+    players: Vec<Player>,
+    /// In time-attack mode, the time since the run started, the time each checkpoint was passed,
+    /// and how much longer the latest split stays on screen.
+    run_time: f32,
+    splits: Vec<f32>,
+    split_display_time: f32,
+    scenery: Scenery,
+    camera: CameraController,
+    /// Sparkles, dust and feathers flying around the level.
+    particles: Particles,
+    /// Descriptions of the sounds that just played, shown if the player turned captions on.
+    captions: Captions,
+    level: Level,
+}
+
+impl GameScreen {
+    /// Sets up a new run. Races are run by `RaceScreen` instead, so this panics if `options`
+    /// asks for one.
+    /// `options`: The mode, difficulty and settings chosen for this run.
+    pub fn new(options: RunOptions) -> GameScreen {
+        let settings = &options.settings;
+        // Create the player characters. With two players, player 1 uses WASD and player 2
+        // uses the arrow keys, and player 2 starts a little to the right with a blue tint.
+        let players: Vec<Player> = match options.players {
+            Players::Single => vec![Player::new(Controls::ARROWS, WHITE, settings.lives, 0.0)],
+            Players::CoOp | Players::Versus => vec![
+                Player::new(Controls::WASD, WHITE, settings.lives, 0.0),
+                Player::new(Controls::ARROWS, PLAYER_TWO_TINT, settings.lives, 60.0),
+            ],
+        };
+        // Generate a new level from a fresh seed, resting on the bottom of the virtual screen,
+        // so the level is the same whatever the size of the window.
+        let seed = (date::now() * 1000.0) as u64;
+        let level = match options.mode {
+            GameMode::Story | GameMode::TimeAttack => {
+                Level::generate(seed, VIRTUAL_HEIGHT, &options.settings)
+            }
+            // Endless levels start empty and are generated chunk by chunk as the player runs.
+            GameMode::Endless => Level::endless(seed, VIRTUAL_HEIGHT),
+            // Races are only ever run online, by `RaceScreen` (see `round_scene`).
+            GameMode::Race => unreachable!("races are run by RaceScreen, not GameScreen"),
+        };
+
+        GameScreen {
+            options,
+            run_time: 0.0,
+            splits: Vec::new(),
+            split_display_time: 0.0,
+            // Create the scrolling background and clouds, in the layers the level asks for.
+            scenery: Scenery::new(level.parallax, level.tile_map.bottom()),
+            camera: CameraController::new(camera_focus(&players).0, level.camera_bounds()),
+            players,
+            particles: Particles::new(),
+            captions: Captions::new(),
+            level,
+        }
+    }
+
+    /// Ends the run, fading to the game over screen.
+    /// `reason`: Why the game ended.
+    fn finish(&self, reason: GameOverReason) -> SceneCommand {
+        SceneCommand::Replace(
+            Box::new(GameOverScreen::new(reason, self.options)),
+            Transition::Fade,
+        )
+    }
+}
+
+impl Scene for GameScreen {
+    fn update(&mut self, context: &mut SceneContext, delta_time: f32) -> SceneCommand {
+        let assets = &context.assets;
+        let settings = &self.options.settings;
+        // Run everything slower when the player picked a lower game speed.
+        let delta_time = delta_time * context.settings.game_speed;
+        let reduced_motion = context.settings.reduced_motion;
+
+        // --- Pause ---
+        // Escape freezes the game under the pause menu until it is pressed again.
+        if is_key_pressed(KeyCode::Escape) {
+            return SceneCommand::Push(Box::new(PauseScreen), Transition::Cut);
+        }
+
+        // --- Handle Player Input ---
+        for player in self.players.iter_mut().filter(|player| !player.is_out()) {
+            player.handle_input(assets, &mut self.particles, &mut self.captions);
+        }
+
+        // --- Update Game State (Physics and Movement) ---
+        {
+            // Advance the time-attack clock.
+            self.run_time += delta_time;
+            self.split_display_time = (self.split_display_time - delta_time).max(0.0);
+
+            // --- Update Platforms ---
+            // Move platforms and advance their timers. Crumbling platforms need to know
+            // whether a player is standing on them.
+            let standing_on: Vec<Entity> = self
+                .players
+                .iter()
+                .filter_map(|player| player.standing_on)
+                .collect();
+            platform_system(&mut self.level.world, &standing_on, delta_time);
+
+            // --- Update Players ---
+            for player in self.players.iter_mut().filter(|player| !player.is_out()) {
+                player.update(
+                    &self.level,
+                    assets,
+                    &mut self.particles,
+                    &mut self.captions,
+                    delta_time,
+                );
+            }
+            // Keep the players close enough together for the camera to frame them both.
+            let (rear_x, front_x) = player_x_range(&self.players);
+            for player in self.players.iter_mut().filter(|player| !player.is_out()) {
+                let rect = &mut player.body.entity.rect;
+                rect.x = rect
+                    .x
+                    .clamp(front_x - MAX_PLAYER_SPREAD, rear_x + MAX_PLAYER_SPREAD);
+            }
+
+            // --- Update Chickens and Other Moving Entities ---
+            // Move everything with a velocity, let stomped chickens recover, then let each
+            // chicken follow its own behaviour (patrolling, swooping, perching, ...),
+            // reacting to whichever player is closest.
+            let player_centers: Vec<Vec2> = self
+                .players
+                .iter()
+                .filter(|player| !player.is_out())
+                .map(|player| player.body.entity.rect.center())
+                .collect();
+            movement_system(&mut self.level.world, delta_time);
+            hazard_system(&mut self.level.world, delta_time);
+            chicken_system(&mut self.level.world, &player_centers, delta_time);
+
+            // --- Update Particles and Captions ---
+            self.particles.update(delta_time);
+            self.captions.update(delta_time);
+
+            // --- Update Camera ---
+            let (focus, facing) = camera_focus(&self.players);
+            let bounds = self.level.camera_bounds();
+            self.camera.follow(focus, facing, bounds, delta_time);
+
+            // --- Update Scenery ---
+            let (rear_x, front_x) = player_x_range(&self.players);
+            let far_behind_players = rear_x - 2.0 * VIRTUAL_WIDTH;
+            self.scenery
+                .update(delta_time, far_behind_players, reduced_motion);
+
+            // --- Stream Endless Level ---
+            if self.options.mode == GameMode::Endless {
+                // Generate chunks ahead of the players, each a bit harder the further they are.
+                while self.level.generated_to < front_x + 1.5 * VIRTUAL_WIDTH {
+                    let ramp = (1.0 + self.level.generated_to / ENDLESS_RAMP_DISTANCE)
+                        .min(ENDLESS_MAX_RAMP);
+                    self.level.extend_endless(settings, ramp);
+                    // Throw away whatever is far behind, so memory use stays flat.
+                    self.level.discard_before(far_behind_players);
+                }
+
+                // Award each player points for the furthest distance they have travelled.
+                for player in &mut self.players {
+                    let travelled = ((player.body.entity.rect.x - PLAYER_START_POS.x)
+                        / ENDLESS_DISTANCE_PER_POINT)
+                        .max(0.0) as u32;
+                    if travelled > player.distance_points {
+                        player.score += travelled - player.distance_points;
+                        player.distance_points = travelled;
+                    }
+                }
+            }
+        }
+
+        // --- Check Collisions and Game Logic ---
+        {
+            // --- Lives ---
+            for index in 0..self.players.len() {
+                if self.players[index].is_out()
+                    || !self.players[index].check_collisions(
+                        &mut self.level,
+                        assets,
+                        &mut self.particles,
+                        &mut self.captions,
+                    )
+                {
+                    continue;
+                }
+                // Lose a life. Start again next to a teammate who is still in the game,
+                // or from the beginning if there is none, keeping the eggs collected.
+                // Endless levels throw away the terrain behind the players, so there the
+                // player starts again on the ground near where they were lost instead.
+                self.players[index].lives -= 1;
+                // Shake the screen, unless the player asked for less motion.
+                if !reduced_motion {
+                    self.camera.shake(CAMERA_HIT_SHAKE);
+                }
+                let fallback = if self.options.mode == GameMode::Endless {
+                    let lost_at = self.players[index].body.entity.rect.center().x;
+                    self.level
+                        .tile_map
+                        .ground_near(lost_at)
+                        .unwrap_or(PLAYER_START_POS)
+                } else {
+                    PLAYER_START_POS
+                };
+                let respawn_at = self
+                    .players
+                    .iter()
+                    .enumerate()
+                    .find(|(other, player)| *other != index && !player.is_out())
+                    .map_or(fallback, |(_, player)| player.body.entity.rect.center());
+                if !self.players[index].is_out() {
+                    self.players[index].respawn(respawn_at);
+                }
+            }
+            // Everyone's score counts towards the team's score.
+            let score = self.players.iter().map(|player| player.score).sum();
+            if self.players.iter().all(Player::is_out) {
+                // No lives left: end the game due to death.
+                return self.finish(GameOverReason::Death { score });
+            }
+
+            // --- Versus Win Condition ---
+            // In versus mode, the first player to collect enough eggs wins.
+            if self.options.players == Players::Versus {
+                if let Some(winner) = self
+                    .players
+                    .iter()
+                    .position(|player| player.eggs_collected >= settings.eggs_needed_for_win)
+                {
+                    let scores = self.players.iter().map(|player| player.score).collect();
+                    return self.finish(GameOverReason::Versus { winner, scores });
+                }
+            }
+
+            // --- Checkpoints ---
+            // Record a split time the first time the leading player passes each checkpoint.
+            let (_, front_x) = player_x_range(&self.players);
+            if self.options.mode == GameMode::TimeAttack
+                && self
+                    .level
+                    .checkpoints
+                    .get(self.splits.len())
+                    .is_some_and(|checkpoint| front_x + PLAYER_SIZE.x / 2.0 >= *checkpoint)
+            {
+                self.splits.push(self.run_time);
+                self.split_display_time = SPLIT_DISPLAY_TIME;
+                play_effect(&assets.magic);
+                self.captions.show(Cue::Magic);
+            }
+
+            // --- House Collision (End/Win Condition) ---
+            // Check if a player collides with the house (endless levels have none).
+            // In versus mode the house is not a goal, since players race for eggs instead.
+            let at_house = self.players.iter().find(|player| {
+                !player.is_out()
+                    && self.options.players != Players::Versus
+                    && player.reached_house(&self.level)
+            });
+            if let Some(player) = at_house {
+                // In co-op mode, the eggs collected by both players count towards the goal.
+                let eggs_collected = match self.options.players {
+                    Players::CoOp => self
+                        .players
+                        .iter()
+                        .map(|player| player.eggs_collected)
+                        .sum(),
+                    Players::Single | Players::Versus => player.eggs_collected,
+                };
+                // In time-attack mode, stop the clock and award a medal.
+                let time = (self.options.mode == GameMode::TimeAttack).then(|| RunTime {
+                    time: self.run_time,
+                    splits: self.splits.clone(),
+                    medal: Medal::for_time(self.run_time, self.level.medal_times),
+                });
+                // Check if the player has enough eggs to win.
+                if eggs_collected >= settings.eggs_needed_for_win {
+                    return self.finish(GameOverReason::Win { score, time }); // Player wins!
+                } else if eggs_collected >= settings.eggs_needed_for_house {
+                    // Player reached the house but needs more eggs.
+                    return self.finish(GameOverReason::End { score, time });
+                }
+                // If player has fewer eggs than needed for the house, nothing happens yet.
+            }
+        }
+
+        SceneCommand::Stay
+    }
+
+    fn draw(&self, context: &SceneContext) {
+        let assets = &context.assets;
+        let settings = &self.options.settings;
+        // --- Draw Everything ---
+        {
+            // Clear the screen with the background color.
+            clear_background(BACKGROUND_COLOR);
+
+            // --- Camera Setup ---
+            let view = self.camera.apply(&context.display);
+
+            // --- Draw World Elements (using camera coordinates) ---
+            self.scenery.draw_behind(assets, &context.display, view);
+            draw_level(&self.level, assets, view, context.settings.high_contrast);
+            // Draw the time-attack checkpoints as flags on poles, turning green once passed.
+            if self.options.mode == GameMode::TimeAttack {
+                for (index, checkpoint) in self.level.checkpoints.iter().enumerate() {
+                    let ground_y = self
+                        .level
+                        .tile_map
+                        .surface_y(*checkpoint)
+                        .unwrap_or(self.level.tile_map.bottom());
+                    let top_y = ground_y - 120.0;
+                    let color = if index < self.splits.len() {
+                        GREEN
+                    } else {
+                        RED
+                    };
+                    draw_line(*checkpoint, ground_y, *checkpoint, top_y, 4.0, WHITE);
+                    draw_triangle(
+                        Vec2::new(*checkpoint, top_y),
+                        Vec2::new(*checkpoint + 40.0, top_y + 15.0),
+                        Vec2::new(*checkpoint, top_y + 30.0),
+                        color,
+                    );
+                }
+            }
+            // Draw the players still in the game.
+            for player in self.players.iter().filter(|player| !player.is_out()) {
+                player.draw(assets);
+            }
+            // Draw the sparkles, dust and feathers over the players.
+            self.particles.draw();
+            // Draw the scenery passing in front of everything.
+            self.scenery.draw_in_front(assets, &context.display, view);
+
+            // --- Draw UI Elements (using screen coordinates) ---
+            // Switch back to the screen camera (no scrolling, fixed to the virtual screen).
+            context.display.set_screen_camera();
+
+            // Draw a score panel for each player: in the top-right corner for a single player,
+            // or top-left for player 1 and top-right for player 2.
+            let team_eggs: u32 = self
+                .players
+                .iter()
+                .map(|player| player.eggs_collected)
+                .sum();
+            // The panels are 256 pixels wide, 51 pixels in from the edge of the screen.
+            let (left_x, right_x) = (51.0, VIRTUAL_WIDTH - 51.0 - 256.0);
+            let strings = context.settings.strings();
+            for (index, player) in self.players.iter().enumerate() {
+                let (panel_x, title) = match self.options.players {
+                    Players::Single => (right_x, fill(strings.score, &[&player.score])),
+                    Players::CoOp | Players::Versus => (
+                        if index == 0 { left_x } else { right_x },
+                        fill(strings.player_score, &[&(index + 1), &player.score]),
+                    ),
+                };
+                // Show the eggs carried towards the winning goal (e.g., "{egg} + 3/5", with the
+                // egg drawn as a picture), shared between the players in co-op mode, or the eggs
+                // and distance so far in endless mode, where there is no goal.
+                let eggs = match self.options.players {
+                    Players::CoOp => team_eggs,
+                    Players::Single | Players::Versus => player.eggs_collected,
+                };
+                let eggs_text = match self.options.mode {
+                    GameMode::Story | GameMode::TimeAttack | GameMode::Race => {
+                        format!("{{egg}} + {}/{}", eggs, settings.eggs_needed_for_win)
+                    }
+                    GameMode::Endless => format!("{{egg}} + {}  {}m", eggs, player.distance_points),
+                };
+                player.draw_hud(assets, strings, panel_x, &title, &eggs_text);
+            }
+
+            // In time-attack mode, draw the run timer at the top centre of the screen,
+            // with the latest split time underneath for a few seconds after each checkpoint.
+            if self.options.mode == GameMode::TimeAttack {
+                let center_x = VIRTUAL_WIDTH / 2.0;
+                draw_text_line(
+                    assets,
+                    &format_time(self.run_time),
+                    center_x,
+                    54.0,
+                    HUD_TEXT.centered().sized(TEXT_SIZE_LARGE),
+                );
+                if let (Some(split), true) = (self.splits.last(), self.split_display_time > 0.0) {
+                    draw_text_line(
+                        assets,
+                        &fill(strings.split, &[&self.splits.len(), &format_time(*split)]),
+                        center_x,
+                        84.0,
+                        HUD_TEXT.centered(),
+                    );
+                }
+            }
+
+            // Describe the sounds that just played, if the player wants captions.
+            if context.settings.captions {
+                self.captions.draw(assets, strings, VIRTUAL_HEIGHT);
+            }
+        }
+    }
+}
+
+/// The left edges of the rear-most and front-most players still in the game.
+/// Falls back to the start position if everyone is out.
+fn player_x_range(players: &[Player]) -> (f32, f32) {
+    players
+        .iter()
+        .filter(|player| !player.is_out())
+        .map(|player| player.body.entity.rect.x)
+        .fold(None, |range: Option<(f32, f32)>, x| match range {
+            Some((rear, front)) => Some((rear.min(x), front.max(x))),
+            None => Some((x, x)),
+        })
+        .unwrap_or((PLAYER_START_POS.x, PLAYER_START_POS.x))
+}
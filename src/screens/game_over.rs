@@ -0,0 +1,251 @@
+//! The game over screen, shown at the end of every run.
+
+use crate::audio::play_effect;
+use crate::captions::{Captions, Cue};
+use crate::constants::*;
+use crate::locale::fill;
+use crate::options::{GameMode, Players, RunOptions};
+use crate::particles::{Particles, CONFETTI};
+use crate::scenes::{Scene, SceneCommand, SceneContext, Transition};
+use crate::scores::{format_time, Medal};
+use crate::screens::{round_scene, GameOverReason};
+use crate::text::{draw_text_line, MENU_TEXT};
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+/// The game over screen, showing how the run ended and the high-score table.
+/// Waits for the player to press 'R' to play another round with the same options.
+pub struct GameOverScreen {
+    reason: GameOverReason,
+    options: RunOptions,
+    /// Which meme to show when the player reached the house without enough eggs.
+    meme: usize,
+    high_scores_text: Option<String>,
+    time_texts: Option<(String, String, Color)>,
+    final_score_text: Option<String>,
+    /// Confetti thrown after a win, how much longer (in seconds) it keeps being thrown,
+    /// and how long until the next burst.
+    confetti: Particles,
+    confetti_time: f32,
+    next_confetti: f32,
+    /// The caption of the ending's sound, shown if the player turned captions on.
+    captions: Captions,
+}
+
+impl GameOverScreen {
+    /// Prepares the screen for a finished run. The texts describing how it went are written
+    /// when the screen is shown, in the chosen language.
+    /// `reason`: The `GameOverReason` enum variant indicating why the game ended.
+    /// `options`: The options the run was played with, so scores are kept per mode and difficulty.
+    pub fn new(reason: GameOverReason, options: RunOptions) -> GameOverScreen {
+        // Only a win is celebrated with confetti.
+        let confetti_time = if matches!(reason, GameOverReason::Win { .. }) {
+            CONFETTI_TIME
+        } else {
+            0.0
+        };
+
+        GameOverScreen {
+            reason,
+            options,
+            meme: 0,
+            high_scores_text: None,
+            time_texts: None,
+            final_score_text: None,
+            confetti: Particles::new(),
+            confetti_time,
+            next_confetti: 0.0,
+            captions: Captions::new(),
+        }
+    }
+
+    /// Whether this run goes in the high-score table. Only single-player runs do,
+    /// so they are compared fairly, and online races have no score to keep.
+    fn keeps_scores(&self) -> bool {
+        self.options.players == Players::Single && self.options.mode != GameMode::Race
+    }
+}
+
+impl Scene for GameOverScreen {
+    fn enter(&mut self, context: &mut SceneContext) {
+        let assets = &context.assets;
+        let options = &self.options;
+        let keeps_scores = self.keeps_scores();
+        if keeps_scores {
+            context.high_scores.record(
+                options.mode,
+                options.difficulty,
+                self.reason.score(),
+                self.reason.time().map(|run_time| run_time.time),
+            );
+        }
+
+        // Play (and caption) a sound effect based on how the game ended.
+        let (sound, cue) = match self.reason {
+            GameOverReason::Death { .. } => (&assets.game_over_sound, Cue::GameOver), // Death sound
+            GameOverReason::End { .. } => (&assets.magic, Cue::Magic), // "Reached end" sound
+            GameOverReason::Win { .. } => (&assets.win_sound, Cue::Win), // Win sound
+            GameOverReason::Versus { .. } => (&assets.win_sound, Cue::Win), // Someone won
+            GameOverReason::RaceFinished { place: 1, .. } => (&assets.win_sound, Cue::Win), // Won the race
+            GameOverReason::RaceFinished { .. } => (&assets.magic, Cue::Magic), // Finished the race
+            GameOverReason::Disconnected { .. } => (&assets.game_over_sound, Cue::GameOver), // Lost the race
+        };
+        play_effect(sound);
+        self.captions.show(cue);
+        // Pick a random meme, in case this ending shows one.
+        self.meme = gen_range(0, assets.meme_textures.len());
+
+        let strings = context.settings.strings();
+        // Prepare the time-attack results: the total time with its medal, and the split times.
+        self.time_texts = self.reason.time().map(|run_time| {
+            let medal = run_time
+                .medal
+                .map_or(strings.no_medal, |medal| medal.label(strings));
+            let splits = run_time
+                .splits
+                .iter()
+                .map(|split| format_time(*split))
+                .collect::<Vec<_>>()
+                .join("  ");
+            (
+                fill(strings.run_time, &[&format_time(run_time.time), &medal]),
+                fill(strings.splits, &[&splits]),
+                run_time.medal.map_or(WHITE, Medal::color),
+            )
+        });
+
+        // Prepare the final score text only if the player died,
+        // or the winner and both scores after a versus game.
+        self.final_score_text = match &self.reason {
+            // If the reason was Death, format the score string.
+            GameOverReason::Death { score } => Some(fill(strings.final_score, &[score])),
+            GameOverReason::Versus { winner, scores } => {
+                let scores = scores
+                    .iter()
+                    .enumerate()
+                    .map(|(index, score)| fill(strings.player_short_score, &[&(index + 1), score]))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                Some(fill(strings.player_wins, &[&(winner + 1), &scores]))
+            }
+            GameOverReason::RaceFinished { place, racers } => {
+                Some(fill(strings.race_place, &[place, racers]))
+            }
+            GameOverReason::Disconnected { reason } => Some(reason.clone()),
+            // Otherwise, there's no score to display on this screen.
+            _ => None,
+        };
+
+        // Prepare the high-score table for this mode and difficulty, e.g. "Story Normal: 12  9  4",
+        // showing times for finished time-attack runs, e.g. "Time Attack Normal: 0:45.67  0:52.10  7".
+        self.high_scores_text = keeps_scores.then(|| {
+            format!(
+                "{} {}: {}",
+                options.mode.label(strings),
+                options.difficulty.label(strings),
+                context
+                    .high_scores
+                    .top(options.mode, options.difficulty)
+                    .map(|entry| match entry.time {
+                        Some(time) => format_time(time),
+                        None => entry.score.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            )
+        });
+    }
+
+    fn update(&mut self, context: &mut SceneContext, delta_time: f32) -> SceneCommand {
+        // --- Confetti ---
+        // After a win, throw bursts of confetti up from along the bottom of the screen for a while.
+        self.confetti_time -= delta_time;
+        self.next_confetti -= delta_time;
+        if self.confetti_time > 0.0 && self.next_confetti <= 0.0 {
+            let x = gen_range(0.1, 0.9) * VIRTUAL_WIDTH;
+            self.confetti.burst(&CONFETTI, Vec2::new(x, VIRTUAL_HEIGHT));
+            self.next_confetti = CONFETTI_INTERVAL;
+        }
+        self.confetti.update(delta_time);
+        self.captions.update(delta_time);
+
+        // Check if the 'R' key was pressed *this frame*, and wipe across to the next round.
+        if is_key_pressed(KeyCode::R) {
+            return SceneCommand::Replace(
+                round_scene(self.options, &context.server_address),
+                Transition::Wipe,
+            );
+        }
+        SceneCommand::Stay
+    }
+
+    fn draw(&self, context: &SceneContext) {
+        let assets = &context.assets;
+        let language = context.settings.language;
+        // Choose the appropriate game over image based on the reason,
+        // translated if an asset pack has it in the chosen language.
+        let game_over = assets
+            .translation("game_over", language)
+            .unwrap_or(&assets.game_over);
+        let win = assets.translation("win", language).unwrap_or(&assets.win);
+        let texture = match self.reason {
+            GameOverReason::Death { .. } => game_over, // Standard game over screen
+            GameOverReason::End { .. } => &assets.meme_textures[self.meme], // The chosen meme
+            GameOverReason::Win { .. } => win,         // Winning screen
+            GameOverReason::Versus { .. } => win,      // Winning screen for the winner
+            GameOverReason::RaceFinished { place: 1, .. } => win, // Winning screen for first place
+            GameOverReason::RaceFinished { .. } => &assets.meme_textures[self.meme], // The chosen meme
+            GameOverReason::Disconnected { .. } => game_over, // Standard game over screen
+        };
+
+        // Clear the screen.
+        clear_background(BACKGROUND_COLOR);
+        // Draw the chosen game over/win/end screen image, scaled to fit.
+        texture.draw(
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(Vec2::new(VIRTUAL_WIDTH, VIRTUAL_HEIGHT)),
+                ..Default::default()
+            },
+        );
+
+        // Draw the confetti over the picture, but under the text.
+        self.confetti.draw();
+
+        // If there's final score text to display (only on Death, versus and race screens)...
+        if let Some(final_score_text) = &self.final_score_text {
+            // Draw it in the space left for it near the top of the picture.
+            draw_text_line(
+                assets,
+                final_score_text,
+                425.0,
+                174.0,
+                MENU_TEXT.sized(TEXT_SIZE_LARGE),
+            );
+        }
+
+        // Draw the time-attack results above the high scores.
+        if let Some((time_text, splits_text, medal_color)) = &self.time_texts {
+            draw_text_line(
+                assets,
+                time_text,
+                51.0,
+                653.0,
+                MENU_TEXT.colored(*medal_color),
+            );
+            draw_text_line(assets, splits_text, 51.0, 691.0, MENU_TEXT);
+        }
+
+        // Draw the best scores for this difficulty along the bottom of the screen.
+        if let Some(high_scores_text) = &self.high_scores_text {
+            draw_text_line(assets, high_scores_text, 51.0, 730.0, MENU_TEXT);
+        }
+
+        // Describe the ending's sound at the top, clear of the results at the bottom.
+        if context.settings.captions {
+            self.captions.draw(assets, context.settings.strings(), 70.0);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! The screens of the game, each a scene on the scene stack, and the drawing they share.
+
+pub mod game;
+pub mod game_over;
+pub mod options;
+pub mod pause;
+pub mod race;
+pub mod start;
+
+use crate::assets::Assets;
+use crate::constants::*;
+use crate::entities::player::{MoveDirection, Player};
+use crate::level::Level;
+use crate::options::{GameMode, RunOptions};
+use crate::scenes::Scene;
+use crate::scores::RunTime;
+use crate::screens::game::GameScreen;
+use crate::screens::race::RaceScreen;
+use crate::world::systems::{draw_system, hazard_outline_system};
+use macroquad::prelude::*;
+
+/// Creates the scene for a round played with `options`: an online race, or a local game.
+/// `options`: The mode, players and difficulty chosen on the start screen.
+/// `server_address`: The race server to join when the Race mode is chosen.
+pub fn round_scene(options: RunOptions, server_address: &str) -> Box<dyn Scene> {
+    match options.mode {
+        GameMode::Race => Box::new(RaceScreen::new(options, server_address)),
+        GameMode::Story | GameMode::Endless | GameMode::TimeAttack => {
+            Box::new(GameScreen::new(options))
+        }
+    }
+}
+
+/// Represents the different reasons why the game might end.
+pub enum GameOverReason {
+    /// Player died (hit enemy, spike, fell off screen) with no lives left. Includes the final score.
+    Death { score: u32 },
+    /// Player reached the house but didn't have enough eggs to win.
+    /// Includes the final score, and the run's timing in time-attack mode.
+    End { score: u32, time: Option<RunTime> },
+    /// Player reached the house with enough eggs.
+    /// Includes the final score, and the run's timing in time-attack mode.
+    Win { score: u32, time: Option<RunTime> },
+    /// One player collected enough eggs first in a versus game.
+    /// Includes the winner's index and both players' scores.
+    Versus { winner: usize, scores: Vec<u32> },
+    /// Player reached the house in an online race.
+    /// Includes the place they finished in (1 = first) and how many were racing.
+    RaceFinished { place: u8, racers: usize },
+    /// The connection to the race server failed. Includes a description of what went wrong.
+    Disconnected { reason: String },
+}
+
+impl GameOverReason {
+    /// The score the run ended with, whatever the reason.
+    pub fn score(&self) -> u32 {
+        match self {
+            GameOverReason::Death { score }
+            | GameOverReason::End { score, .. }
+            | GameOverReason::Win { score, .. } => *score,
+            GameOverReason::Versus { scores, .. } => scores.iter().copied().max().unwrap_or(0),
+            // Races are about finishing first, not scoring points.
+            GameOverReason::RaceFinished { .. } | GameOverReason::Disconnected { .. } => 0,
+        }
+    }
+
+    /// The run's timing, if it was a time-attack run that reached the house.
+    pub fn time(&self) -> Option<&RunTime> {
+        match self {
+            GameOverReason::Death { .. }
+            | GameOverReason::Versus { .. }
+            | GameOverReason::RaceFinished { .. }
+            | GameOverReason::Disconnected { .. } => None,
+            GameOverReason::End { time, .. } | GameOverReason::Win { time, .. } => time.as_ref(),
+        }
+    }
+}
+
+/// Draws everything in the level except the players: terrain, then every entity with a sprite
+/// (platforms, the house, eggs, spikes and chickens). Expects the world camera to be set.
+/// `view`: The area of the world on screen, so only visible terrain and entities are drawn.
+/// `high_contrast`: Whether to outline spikes and chickens, so they stand out.
+pub fn draw_level(level: &Level, assets: &Assets, view: Rect, high_contrast: bool) {
+    // Draw the tile terrain that is on screen.
+    level.tile_map.draw(assets, view);
+    // Draw the entities on screen, layer by layer.
+    draw_system(&level.world, assets, view);
+    if high_contrast {
+        hazard_outline_system(&level.world, view);
+    }
+}
+
+/// What the camera should follow: the area around every player still in the game, and the
+/// direction they face (1.0 for right, -1.0 for left, 0.0 when they face different ways).
+/// With nobody left in the game, the start of the level.
+pub fn camera_focus(players: &[Player]) -> (Rect, f32) {
+    let mut focus: Option<Rect> = None;
+    let mut facing = 0.0;
+    for player in players.iter().filter(|player| !player.is_out()) {
+        let rect = player.body.entity.rect;
+        focus = Some(focus.map_or(rect, |focus| focus.combine_with(rect)));
+        facing += match player.direction {
+            MoveDirection::Left => -1.0,
+            MoveDirection::Right => 1.0,
+        };
+    }
+    let start = Rect::new(
+        PLAYER_START_POS.x,
+        PLAYER_START_POS.y,
+        PLAYER_SIZE.x,
+        PLAYER_SIZE.y,
+    );
+    // Players facing opposite ways cancel out, so the camera doesn't favour either.
+    (focus.unwrap_or(start), f32::clamp(facing, -1.0, 1.0))
+}
@@ -0,0 +1,108 @@
+//! The options screen, shown over the start screen, where the player's settings are changed:
+//! the language and the accessibility options.
+
+use crate::constants::*;
+use crate::scenes::{Scene, SceneCommand, SceneContext, Transition};
+use crate::settings::Settings;
+use crate::text::{draw_text_line, MENU_TEXT, TITLE_TEXT};
+use macroquad::prelude::*;
+
+/// Lists the settings. Up/Down picks one, Left/Right changes it (every change is saved straight
+/// away), and Escape goes back to the start screen.
+pub struct OptionsScreen {
+    /// Which setting is picked, by its index in `Settings::fields`.
+    selected: usize,
+}
+
+impl OptionsScreen {
+    /// Starts with the first setting picked.
+    pub fn new() -> OptionsScreen {
+        OptionsScreen { selected: 0 }
+    }
+}
+
+impl Default for OptionsScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene for OptionsScreen {
+    fn update(&mut self, context: &mut SceneContext, _delta_time: f32) -> SceneCommand {
+        if is_key_pressed(KeyCode::Escape) {
+            return SceneCommand::Pop(Transition::Cut);
+        }
+        let field_count = context.settings.fields().len();
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + field_count - 1) % field_count;
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % field_count;
+        }
+        if is_key_pressed(KeyCode::Left) {
+            context.settings.adjust_field(self.selected, -1);
+        }
+        if is_key_pressed(KeyCode::Right) {
+            context.settings.adjust_field(self.selected, 1);
+        }
+        SceneCommand::Stay
+    }
+
+    fn draw(&self, context: &SceneContext) {
+        let assets = &context.assets;
+        let strings = context.settings.strings();
+        // Shade the start screen underneath, like the pause menu does the game.
+        draw_rectangle(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT, PAUSE_SHADE);
+        draw_text_line(
+            assets,
+            strings.options_title,
+            VIRTUAL_WIDTH / 2.0,
+            180.0,
+            TITLE_TEXT.centered(),
+        );
+
+        // Each setting's name on the left and its value on the right, highlighting the picked one.
+        for (index, (label, value)) in context.settings.fields().iter().enumerate() {
+            let color = if index == self.selected {
+                YELLOW
+            } else {
+                WHITE
+            };
+            let y = 280.0 + 50.0 * index as f32;
+            draw_text_line(assets, label, 262.0, y, MENU_TEXT.colored(color));
+            draw_text_line(
+                assets,
+                value,
+                VIRTUAL_WIDTH - 262.0,
+                y,
+                MENU_TEXT.colored(color).right_aligned(),
+            );
+        }
+
+        // Online races ignore the game speed, so say so rather than let it silently do nothing.
+        if self.selected == Settings::GAME_SPEED_FIELD {
+            draw_text_line(
+                assets,
+                strings.game_speed_note,
+                VIRTUAL_WIDTH / 2.0,
+                545.0,
+                MENU_TEXT
+                    .centered()
+                    .sized(TEXT_SIZE_SMALL)
+                    .colored(LIGHTGRAY),
+            );
+        }
+
+        draw_text_line(
+            assets,
+            strings.options_help,
+            VIRTUAL_WIDTH / 2.0,
+            600.0,
+            MENU_TEXT.centered().sized(TEXT_SIZE_SMALL),
+        );
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
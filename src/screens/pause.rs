@@ -0,0 +1,44 @@
+//! The pause menu, shown over the frozen game.
+
+use crate::constants::*;
+use crate::scenes::{Scene, SceneCommand, SceneContext, Transition};
+use crate::text::{draw_text_line, MENU_TEXT, TITLE_TEXT};
+use macroquad::prelude::*;
+
+/// Pauses the game underneath it until Escape is pressed again.
+pub struct PauseScreen;
+
+impl Scene for PauseScreen {
+    fn update(&mut self, _context: &mut SceneContext, _delta_time: f32) -> SceneCommand {
+        // Check if the 'Escape' key was pressed *this frame*, and return to the game.
+        if is_key_pressed(KeyCode::Escape) {
+            return SceneCommand::Pop(Transition::Cut);
+        }
+        SceneCommand::Stay
+    }
+
+    fn draw(&self, context: &SceneContext) {
+        // Shade the game underneath, and say how to carry on.
+        draw_rectangle(0.0, 0.0, VIRTUAL_WIDTH, VIRTUAL_HEIGHT, PAUSE_SHADE);
+        let strings = context.settings.strings();
+        let center_x = VIRTUAL_WIDTH / 2.0;
+        draw_text_line(
+            &context.assets,
+            strings.paused,
+            center_x,
+            VIRTUAL_HEIGHT * 0.45,
+            TITLE_TEXT.centered(),
+        );
+        draw_text_line(
+            &context.assets,
+            strings.resume_hint,
+            center_x,
+            VIRTUAL_HEIGHT * 0.52,
+            MENU_TEXT.centered(),
+        );
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
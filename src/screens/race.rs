@@ -0,0 +1,309 @@
+//! The online race screen.
+
+use crate::audio::play_effect;
+use crate::camera::CameraController;
+use crate::captions::{Captions, Cue};
+use crate::constants::*;
+use crate::entities::platform::platform_system;
+use crate::entities::player::{Controls, MoveDirection, Player, PlayerInput};
+use crate::level::Level;
+use crate::locale::fill;
+use crate::net::{race_level, JoiningRace, RaceConnection};
+use crate::options::RunOptions;
+use crate::parallax::Scenery;
+use crate::particles::Particles;
+use crate::scenes::{Scene, SceneCommand, SceneContext, Transition};
+use crate::screens::game_over::GameOverScreen;
+use crate::screens::start::StartScreen;
+use crate::screens::{camera_focus, draw_level, GameOverReason};
+use crate::text::{draw_text_line, HUD_TEXT, MENU_TEXT};
+use crate::world::Entity;
+use macroquad::prelude::*;
+
+/// An online race against everyone else connected to the race server.
+/// The local player uses the arrow keys and is predicted locally; the other racers are drawn
+/// where the server last saw them, smoothed out between updates.
+/// Moves on to the game over screen when the player reaches the house, or the connection fails,
+/// and back to the start screen if the player leaves with Escape.
+pub struct RaceScreen {
+    options: RunOptions,
+    /// The race server's address, e.g. "127.0.0.1:7777".
+    address: String,
+    /// The request to join the race, while waiting for the server to answer.
+    joining: Option<JoiningRace>,
+    /// The race, once the server has let us join it.
+    race: Option<Race>,
+}
+
+/// A race in progress.
+struct Race {
+    connection: RaceConnection,
+    level: Level,
+    scenery: Scenery,
+    camera: CameraController,
+    /// Dust kicked up by the local player.
+    particles: Particles,
+    /// Descriptions of the local player's sounds, shown if the player turned captions on.
+    captions: Captions,
+    player: Player,
+    /// The other racers, drawn with player 2's tint: their ids, characters,
+    /// and where the server last saw them.
+    others: Vec<(u8, Player, Vec2)>,
+    /// When the last update from the server arrived, to notice when it goes away.
+    last_heard: f64,
+    /// The server's race time: as of its last update, plus the time since.
+    server_time: f64,
+    /// How far (in seconds) this copy of the level's platforms has been moved. Platforms are
+    /// moved to catch up with `server_time` rather than by each frame's time, so they stay
+    /// where the server has them and landings on them aren't corrected over and over.
+    platform_time: f64,
+}
+
+impl RaceScreen {
+    /// Prepares to join the race at `address`. Connecting happens once the screen is shown.
+    /// `options`: The options the race was started with, to play again with afterwards.
+    /// `address`: The race server's address, e.g. "127.0.0.1:7777".
+    pub fn new(options: RunOptions, address: &str) -> RaceScreen {
+        RaceScreen {
+            options,
+            address: address.to_owned(),
+            joining: None,
+            race: None,
+        }
+    }
+
+    /// Ends the race, fading to the game over screen.
+    /// `reason`: Why the race ended.
+    fn finish(&self, reason: GameOverReason) -> SceneCommand {
+        SceneCommand::Replace(
+            Box::new(GameOverScreen::new(reason, self.options)),
+            Transition::Fade,
+        )
+    }
+}
+
+impl Scene for RaceScreen {
+    fn exit(&mut self, _context: &mut SceneContext) {
+        // Let the server know we've gone, whether we finished or gave up.
+        if let Some(race) = &self.race {
+            race.connection.leave();
+        }
+    }
+
+    fn update(&mut self, context: &mut SceneContext, delta_time: f32) -> SceneCommand {
+        // Unlike the other modes, races ignore `settings.game_speed`: every racer shares the
+        // server's clock, so a client running slower would only be corrected back by it.
+        // The options screen tells players this under the setting.
+        let assets = &context.assets;
+        let strings = context.settings.strings();
+
+        // --- Leave the Race ---
+        // Escape goes back to the start screen, whether still joining or racing.
+        // Leaving the screen tells the server we've gone (see `exit`).
+        if is_key_pressed(KeyCode::Escape) {
+            return SceneCommand::Replace(Box::new(StartScreen::new()), Transition::Fade);
+        }
+
+        // --- Join the Race ---
+        // Joining can take a moment, so check on it once a frame while "Connecting..." shows.
+        let Some(race) = &mut self.race else {
+            let joined = match &mut self.joining {
+                Some(joining) => joining.poll(),
+                None => JoiningRace::start(&self.address).map(|joining| {
+                    self.joining = Some(joining);
+                    None
+                }),
+            };
+            match joined {
+                Ok(None) => {}
+                Ok(Some(connection)) => {
+                    self.joining = None;
+                    let level = race_level(connection.seed);
+                    let player = Player::new(Controls::ARROWS, WHITE, 1, 0.0);
+                    self.race = Some(Race {
+                        scenery: Scenery::new(level.parallax, level.tile_map.bottom()),
+                        camera: CameraController::new(
+                            camera_focus(std::slice::from_ref(&player)).0,
+                            level.camera_bounds(),
+                        ),
+                        level,
+                        connection,
+                        particles: Particles::new(),
+                        captions: Captions::new(),
+                        player,
+                        others: Vec::new(),
+                        last_heard: get_time(),
+                        // The platforms stay put until the server says how far along they are.
+                        server_time: 0.0,
+                        platform_time: 0.0,
+                    });
+                }
+                Err(error) => {
+                    return self.finish(GameOverReason::Disconnected {
+                        reason: fill(strings.join_failed, &[&self.address, &error]),
+                    });
+                }
+            }
+            return SceneCommand::Stay;
+        };
+
+        // The server's clock keeps running however long the frame was...
+        race.server_time += delta_time as f64;
+        // ...but long frames are capped for the player, just like the server caps them.
+        let delta_time = delta_time.min(MAX_INPUT_DELTA);
+
+        // --- Predict the Local Player ---
+        let player = &mut race.player;
+        let input = PlayerInput::read(player.controls);
+        if player.apply_input(input) {
+            play_effect(&assets.jump);
+            race.captions.show(Cue::Jump);
+            player.kick_up_dust(&mut race.particles);
+        }
+        let standing_on: Vec<Entity> = player.standing_on.into_iter().collect();
+        if race.server_time > race.platform_time {
+            let step = race.server_time - race.platform_time;
+            platform_system(&mut race.level.world, &standing_on, step as f32);
+            race.platform_time = race.server_time;
+        }
+        player.update(
+            &race.level,
+            assets,
+            &mut race.particles,
+            &mut race.captions,
+            delta_time,
+        );
+        race.particles.update(delta_time);
+        race.captions.update(delta_time);
+        // Falling into a pit or touching a spike sends the racer back to the start.
+        if player.fell_out(&race.level) || player.touches_spike(&race.level) {
+            play_effect(&assets.spike_hit);
+            race.captions.show(Cue::SpikeHit);
+            player.respawn(PLAYER_START_POS);
+            if !context.settings.reduced_motion {
+                race.camera.shake(CAMERA_HIT_SHAKE);
+            }
+        }
+        if let Err(error) = race.connection.send_input(input, delta_time) {
+            return self.finish(GameOverReason::Disconnected {
+                reason: fill(strings.connection_lost, &[&error]),
+            });
+        }
+
+        // --- Apply the Server's State ---
+        let racers = match race.connection.receive() {
+            Ok(racers) => racers,
+            Err(error) => {
+                return self.finish(GameOverReason::Disconnected {
+                    reason: fill(strings.connection_lost, &[&error]),
+                })
+            }
+        };
+        if let Some((race_time, racers)) = racers {
+            race.last_heard = get_time();
+            race.server_time = race_time;
+            for racer in &racers {
+                if racer.player_id == race.connection.player_id {
+                    race.connection
+                        .reconcile(&mut race.player, racer, &race.level);
+                    if racer.place != 0 {
+                        return self.finish(GameOverReason::RaceFinished {
+                            place: racer.place,
+                            racers: racers.len(),
+                        });
+                    }
+                } else if !race.others.iter().any(|(id, _, _)| *id == racer.player_id) {
+                    // A new racer: start drawing them where the server says they are.
+                    let mut other = Player::new(Controls::ARROWS, PLAYER_TWO_TINT, 1, 0.0);
+                    other.body.entity.rect.x = racer.position.x;
+                    other.body.entity.rect.y = racer.position.y;
+                    race.others.push((racer.player_id, other, racer.position));
+                }
+            }
+            // Forget racers who left, and aim the rest at their new positions.
+            race.others
+                .retain(|(id, _, _)| racers.iter().any(|racer| racer.player_id == *id));
+            for (id, other, target) in &mut race.others {
+                if let Some(racer) = racers.iter().find(|racer| racer.player_id == *id) {
+                    *target = racer.position;
+                    if racer.velocity.x < 0.0 {
+                        other.direction = MoveDirection::Left;
+                    } else if racer.velocity.x > 0.0 {
+                        other.direction = MoveDirection::Right;
+                    }
+                }
+            }
+        } else if get_time() - race.last_heard > CLIENT_TIMEOUT as f64 {
+            return self.finish(GameOverReason::Disconnected {
+                reason: strings.server_silent.to_string(),
+            });
+        }
+        // Slide the other racers towards where the server last saw them, so they move smoothly
+        // even though updates arrive less often than frames are drawn.
+        let smoothing = (REMOTE_PLAYER_SMOOTHING * delta_time).min(1.0);
+        for (_, other, target) in &mut race.others {
+            let rect = &mut other.body.entity.rect;
+            rect.x += (target.x - rect.x) * smoothing;
+            rect.y += (target.y - rect.y) * smoothing;
+        }
+        let (focus, facing) = camera_focus(std::slice::from_ref(&race.player));
+        race.camera
+            .follow(focus, facing, race.level.camera_bounds(), delta_time);
+        race.scenery.update(
+            delta_time,
+            race.player.body.entity.rect.x - 2.0 * VIRTUAL_WIDTH,
+            context.settings.reduced_motion,
+        );
+        SceneCommand::Stay
+    }
+
+    fn draw(&self, context: &SceneContext) {
+        let assets = &context.assets;
+        let strings = context.settings.strings();
+        clear_background(BACKGROUND_COLOR);
+
+        // Show that we're connecting until the server lets us in.
+        let Some(race) = &self.race else {
+            draw_text_line(
+                assets,
+                &fill(strings.connecting, &[&self.address]),
+                VIRTUAL_WIDTH / 2.0,
+                VIRTUAL_HEIGHT / 2.0,
+                MENU_TEXT.centered(),
+            );
+            draw_text_line(
+                assets,
+                strings.leave_race_hint,
+                VIRTUAL_WIDTH / 2.0,
+                VIRTUAL_HEIGHT * 0.57,
+                MENU_TEXT.centered(),
+            );
+            return;
+        };
+
+        // --- Draw Everything ---
+        let view = race.camera.apply(&context.display);
+        race.scenery.draw_behind(assets, &context.display, view);
+        draw_level(&race.level, assets, view, context.settings.high_contrast);
+        for (_, other, _) in &race.others {
+            other.draw(assets);
+        }
+        race.player.draw(assets);
+        race.particles.draw();
+        race.scenery.draw_in_front(assets, &context.display, view);
+
+        context.display.set_screen_camera();
+        draw_text_line(
+            assets,
+            &fill(strings.racers, &[&(race.others.len() + 1)]),
+            51.0,
+            54.0,
+            HUD_TEXT.sized(TEXT_SIZE),
+        );
+        draw_text_line(assets, strings.race_goal, 51.0, 84.0, HUD_TEXT);
+        draw_text_line(assets, strings.leave_race_hint, 51.0, 114.0, HUD_TEXT);
+        if context.settings.captions {
+            race.captions.draw(assets, strings, VIRTUAL_HEIGHT);
+        }
+    }
+}
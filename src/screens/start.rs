@@ -0,0 +1,208 @@
+//! The start screen, where the mode, players and difficulty are chosen.
+
+use crate::audio::play_music;
+use crate::constants::*;
+use crate::locale::fill;
+use crate::options::{Difficulty, DifficultySettings, GameMode, Players, RunOptions};
+use crate::scenes::{Scene, SceneCommand, SceneContext, Transition};
+use crate::screens::options::OptionsScreen;
+use crate::screens::round_scene;
+use crate::text::{draw_text_line, MENU_TEXT};
+use macroquad::prelude::*;
+
+/// The initial start screen. Waits for the player to press 'P' to begin.
+/// The player picks a mode with 'M', the number of players with 'C',
+/// a difficulty with the number keys (1-4) or Left/Right,
+/// and can tweak the Custom difficulty's settings with Up/Down (choose a setting) and +/- (change it).
+/// 'O' opens the options screen, for the language and accessibility settings.
+pub struct StartScreen {
+    mode: GameMode,
+    players: Players,
+    selected: usize,
+    custom: DifficultySettings,
+    custom_field: usize,
+}
+
+impl StartScreen {
+    /// Starts on Story mode and Normal difficulty, with the Custom settings starting from Normal's values.
+    pub fn new() -> StartScreen {
+        StartScreen {
+            mode: GameMode::Story,
+            players: Players::Single,
+            selected: 1,
+            custom: DifficultySettings::NORMAL,
+            custom_field: 0,
+        }
+    }
+
+    /// The options chosen so far, to play with.
+    fn options(&self) -> RunOptions {
+        let difficulty = Difficulty::ALL[self.selected];
+        let settings = match difficulty {
+            Difficulty::Easy => DifficultySettings::EASY,
+            Difficulty::Normal => DifficultySettings::NORMAL,
+            Difficulty::Hard => DifficultySettings::HARD,
+            Difficulty::Custom => self.custom,
+        };
+        RunOptions {
+            mode: self.mode,
+            players: self.players,
+            difficulty,
+            settings,
+        }
+    }
+}
+
+impl Default for StartScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene for StartScreen {
+    fn enter(&mut self, context: &mut SceneContext) {
+        // Start playing the background music on loop.
+        play_music(&context.assets.background_music);
+    }
+
+    fn update(&mut self, context: &mut SceneContext, _delta_time: f32) -> SceneCommand {
+        // Check if the 'P' key was pressed *this frame*, and fade into the first round.
+        if is_key_pressed(KeyCode::P) {
+            let options = self.options();
+            return SceneCommand::Replace(
+                round_scene(options, &context.server_address),
+                Transition::Fade,
+            );
+        }
+
+        // --- Mode Selection ---
+        if is_key_pressed(KeyCode::M) {
+            let index = GameMode::ALL
+                .iter()
+                .position(|m| *m == self.mode)
+                .unwrap_or(0);
+            self.mode = GameMode::ALL[(index + 1) % GameMode::ALL.len()];
+        }
+        if is_key_pressed(KeyCode::C) {
+            let index = Players::ALL
+                .iter()
+                .position(|p| *p == self.players)
+                .unwrap_or(0);
+            self.players = Players::ALL[(index + 1) % Players::ALL.len()];
+        }
+
+        // --- Options ---
+        // 'O' opens the language and accessibility options over the start screen.
+        if is_key_pressed(KeyCode::O) {
+            return SceneCommand::Push(Box::new(OptionsScreen::new()), Transition::Cut);
+        }
+
+        // --- Difficulty Selection ---
+        let number_keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+        if let Some(index) = number_keys.iter().position(|key| is_key_pressed(*key)) {
+            self.selected = index;
+        }
+        if is_key_pressed(KeyCode::Left) {
+            self.selected = (self.selected + Difficulty::ALL.len() - 1) % Difficulty::ALL.len();
+        }
+        if is_key_pressed(KeyCode::Right) {
+            self.selected = (self.selected + 1) % Difficulty::ALL.len();
+        }
+        // Only the Custom difficulty's settings can be changed.
+        if Difficulty::ALL[self.selected] == Difficulty::Custom {
+            let field_count = self.custom.custom_fields(context.settings.strings()).len();
+            if is_key_pressed(KeyCode::Up) {
+                self.custom_field = (self.custom_field + field_count - 1) % field_count;
+            }
+            if is_key_pressed(KeyCode::Down) {
+                self.custom_field = (self.custom_field + 1) % field_count;
+            }
+            if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+                self.custom.adjust_custom_field(self.custom_field, 1);
+            }
+            if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+                self.custom.adjust_custom_field(self.custom_field, -1);
+            }
+        }
+        SceneCommand::Stay
+    }
+
+    fn draw(&self, context: &SceneContext) {
+        // Clear the screen with the background color.
+        clear_background(BACKGROUND_COLOR);
+        // Draw the start screen image (translated, if an asset pack has it in this language),
+        // scaled to fit the window.
+        let assets = &context.assets;
+        let strings = context.settings.strings();
+        let picture = assets
+            .translation("game_start", context.settings.language)
+            .unwrap_or(&assets.game_start);
+        picture.draw(
+            0.0,   // Draw at top-left corner (x=0)
+            0.0,   // Draw at top-left corner (y=0)
+            WHITE, // No tint
+            DrawTextureParams {
+                // Scale the image to fill the entire screen width and height
+                dest_size: Some(Vec2::new(VIRTUAL_WIDTH, VIRTUAL_HEIGHT)),
+                ..Default::default() // Use defaults for other parameters
+            },
+        );
+
+        // Draw the chosen players and mode above the difficulty choices,
+        // and how to open the options in the top corner.
+        let left_x = 102.0;
+        draw_text_line(
+            assets,
+            &fill(strings.players_choice, &[&self.players.label(strings)]),
+            left_x,
+            653.0,
+            MENU_TEXT,
+        );
+        draw_text_line(
+            assets,
+            &fill(strings.mode_choice, &[&self.mode.label(strings)]),
+            left_x,
+            691.0,
+            MENU_TEXT,
+        );
+        draw_text_line(
+            assets,
+            strings.options_hint,
+            VIRTUAL_WIDTH - 20.0,
+            36.0,
+            MENU_TEXT.right_aligned().sized(TEXT_SIZE_SMALL),
+        );
+        // Draw the difficulty choices along the bottom, highlighting the selected one.
+        for (index, option) in Difficulty::ALL.iter().enumerate() {
+            let color = if index == self.selected {
+                YELLOW
+            } else {
+                WHITE
+            };
+            draw_text_line(
+                assets,
+                &format!("{}. {}", index + 1, option.label(strings)),
+                left_x + 225.0 * index as f32,
+                730.0,
+                MENU_TEXT.colored(color),
+            );
+        }
+        // When Custom is selected, list its settings above the choices.
+        if Difficulty::ALL[self.selected] == Difficulty::Custom {
+            for (index, (label, value)) in self.custom.custom_fields(strings).iter().enumerate() {
+                let color = if index == self.custom_field {
+                    YELLOW
+                } else {
+                    WHITE
+                };
+                draw_text_line(
+                    assets,
+                    &format!("{label}: {value}"),
+                    left_x,
+                    476.0 + 35.0 * index as f32,
+                    MENU_TEXT.colored(color),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! The player's settings, kept between games: unlike the run options, these don't change how a
+//! run is played, only how the game is shown and how fast it runs, so every player can pick
+//! what suits them.
+
+use crate::constants::*;
+use crate::locale::{Language, Strings};
+
+/// The player's settings, saved to `SETTINGS_FILE` between games.
+pub struct Settings {
+    /// The language the game's text (and any translated pictures) is shown in.
+    pub language: Language,
+    /// Whether spikes and chickens are outlined in bright colors, so they are easy to tell apart
+    /// from the scenery.
+    pub high_contrast: bool,
+    /// Whether the screen keeps still when a player gets hurt, and the clouds drift slowly.
+    pub reduced_motion: bool,
+    /// How fast the game runs, from `MIN_GAME_SPEED` to 1.0 (full speed). Online races always
+    /// run at full speed, since every racer shares the server's clock.
+    pub game_speed: f32,
+    /// Whether sounds are described in captions at the bottom of the screen.
+    pub captions: bool,
+}
+
+impl Settings {
+    /// The index of the game speed in `fields`.
+    pub const GAME_SPEED_FIELD: usize = 3;
+
+    /// Loads the settings from disk. Anything missing or unreadable keeps its default
+    /// (for example when running in a web browser, where there is no file system).
+    pub fn load() -> Settings {
+        let mut settings = Settings {
+            language: Language::from_environment(),
+            high_contrast: false,
+            reduced_motion: false,
+            game_speed: 1.0,
+            captions: false,
+        };
+        let contents = std::fs::read_to_string(SETTINGS_FILE).unwrap_or_default();
+        // Each line looks like "language = pt" or "captions = on".
+        // Lines that don't parse are skipped.
+        for line in contents.lines() {
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim() {
+                "language" => {
+                    if let Some(language) = Language::from_code(value) {
+                        settings.language = language;
+                    }
+                }
+                "high_contrast" => settings.high_contrast = value == "on",
+                "reduced_motion" => settings.reduced_motion = value == "on",
+                "game_speed" => {
+                    if let Ok(speed) = value.parse::<f32>() {
+                        settings.game_speed = speed.clamp(MIN_GAME_SPEED, 1.0);
+                    }
+                }
+                "captions" => settings.captions = value == "on",
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    /// Saves the settings, so the next game starts with them.
+    pub fn save(&self) {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        let contents = format!(
+            "language = {}\nhigh_contrast = {}\nreduced_motion = {}\ngame_speed = {:.1}\ncaptions = {}\n",
+            self.language.code(),
+            on_off(self.high_contrast),
+            on_off(self.reduced_motion),
+            self.game_speed,
+            on_off(self.captions),
+        );
+        // Failing to save is not worth interrupting the game for.
+        let _ = std::fs::write(SETTINGS_FILE, contents);
+    }
+
+    /// The words the game shows, in the chosen language.
+    pub fn strings(&self) -> &'static Strings {
+        self.language.strings()
+    }
+
+    /// The settings the player can change on the options screen, as (label, current value) pairs.
+    pub fn fields(&self) -> [(&'static str, String); 5] {
+        let strings = self.strings();
+        let labels = strings.setting_names;
+        let on_off = |on: bool| if on { strings.on } else { strings.off }.to_owned();
+        [
+            (labels[0], self.language.name().to_owned()),
+            (labels[1], on_off(self.high_contrast)),
+            (labels[2], on_off(self.reduced_motion)),
+            (labels[3], format!("{:.0}%", self.game_speed * 100.0)),
+            (labels[4], on_off(self.captions)),
+        ]
+    }
+
+    /// Changes one of the settings (by its index in `fields`) up or down one step, and saves.
+    /// `step`: `1` to go to the next value, `-1` to go back. Settings that are on or off
+    /// switch either way.
+    pub fn adjust_field(&mut self, field: usize, step: i32) {
+        match field {
+            0 => {
+                let count = Language::ALL.len() as i32;
+                let index = Language::ALL
+                    .iter()
+                    .position(|l| *l == self.language)
+                    .unwrap_or(0) as i32;
+                self.language = Language::ALL[(index + step).rem_euclid(count) as usize];
+            }
+            1 => self.high_contrast = !self.high_contrast,
+            2 => self.reduced_motion = !self.reduced_motion,
+            Settings::GAME_SPEED_FIELD => {
+                let speed = self.game_speed + step as f32 * GAME_SPEED_STEP;
+                // Round to the step, so adding it up many times doesn't drift.
+                let speed = (speed / GAME_SPEED_STEP).round() * GAME_SPEED_STEP;
+                self.game_speed = speed.clamp(MIN_GAME_SPEED, 1.0);
+            }
+            _ => self.captions = !self.captions,
+        }
+        self.save();
+    }
+}
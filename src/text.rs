@@ -0,0 +1,237 @@
+//! Drawing text in the game's font, lined up left, centred or right, with an outline or a
+//! shadow to keep it readable over busy pictures.
+//!
+//! Text can show small pictures inline, written as their name in braces: "{egg} + 3/5" draws
+//! the egg sprite followed by " + 3/5". (The built-in font can't draw emoji, and a sprite
+//! matches the rest of the art anyway.)
+//!
+//! Sizes and positions are in virtual screen pixels, like everything else drawn on screen,
+//! so text scales along with the rest of the game.
+
+use crate::assets::Assets;
+use crate::atlas::TextureRegion;
+use crate::constants::*;
+use macroquad::prelude::*;
+
+/// Which part of the text lines up with the x position it's drawn at.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Align {
+    /// The text starts at x.
+    Left,
+    /// The middle of the text is at x.
+    Center,
+    /// The text ends at x.
+    Right,
+}
+
+/// How a piece of text looks.
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    /// The height of the text, in virtual screen pixels.
+    pub size: f32,
+    /// The color of the letters.
+    pub color: Color,
+    /// Which part of the text lines up with the x position.
+    pub align: Align,
+    /// The color of a thin outline around the letters, if any.
+    pub outline: Option<Color>,
+    /// The color of a shadow behind the letters, down and to the right, if any.
+    pub shadow: Option<Color>,
+}
+
+impl TextStyle {
+    /// Plain, left-aligned text.
+    pub const fn new(size: f32, color: Color) -> TextStyle {
+        TextStyle {
+            size,
+            color,
+            align: Align::Left,
+            outline: None,
+            shadow: None,
+        }
+    }
+
+    /// The same style, centred on the x position.
+    pub const fn centered(self) -> TextStyle {
+        TextStyle {
+            align: Align::Center,
+            ..self
+        }
+    }
+
+    /// The same style, ending at the x position.
+    pub const fn right_aligned(self) -> TextStyle {
+        TextStyle {
+            align: Align::Right,
+            ..self
+        }
+    }
+
+    /// The same style at another size.
+    pub const fn sized(self, size: f32) -> TextStyle {
+        TextStyle { size, ..self }
+    }
+
+    /// The same style in another color, e.g. to highlight a menu choice.
+    pub const fn colored(self, color: Color) -> TextStyle {
+        TextStyle { color, ..self }
+    }
+
+    /// The same style with an outline around the letters.
+    pub const fn outlined(self, color: Color) -> TextStyle {
+        TextStyle {
+            outline: Some(color),
+            ..self
+        }
+    }
+
+    /// The same style with a shadow behind the letters.
+    pub const fn shadowed(self, color: Color) -> TextStyle {
+        TextStyle {
+            shadow: Some(color),
+            ..self
+        }
+    }
+}
+
+/// The text of the score panels and the other in-game displays.
+pub const HUD_TEXT: TextStyle = TextStyle::new(TEXT_SIZE_SMALL, WHITE).shadowed(TEXT_SHADOW_COLOR);
+/// The text of menus and the lines on the start and game over screens.
+pub const MENU_TEXT: TextStyle = TextStyle::new(TEXT_SIZE, WHITE).outlined(TEXT_OUTLINE_COLOR);
+/// Big headings, like "Paused".
+pub const TITLE_TEXT: TextStyle =
+    TextStyle::new(TEXT_SIZE_TITLE, WHITE).outlined(TEXT_OUTLINE_COLOR);
+
+/// One part of a line of text: some letters, or an inline picture.
+enum Piece<'a> {
+    Letters(&'a str),
+    Icon(&'a TextureRegion),
+}
+
+/// The picture an inline "{name}" stands for, if there is one by that name.
+fn icon<'a>(assets: &'a Assets, name: &str) -> Option<&'a TextureRegion> {
+    match name {
+        "egg" => Some(&assets.egg),
+        "chicken" => Some(&assets.chicken),
+        "spike" => Some(&assets.spike),
+        _ => None,
+    }
+}
+
+/// Splits `text` into letters and inline pictures. Braces that don't name a picture are kept
+/// as letters.
+fn pieces<'a>(assets: &'a Assets, text: &'a str) -> Vec<Piece<'a>> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|close| open + close) else {
+            break;
+        };
+        match icon(assets, &rest[open + 1..close]) {
+            Some(region) => {
+                if open > 0 {
+                    pieces.push(Piece::Letters(&rest[..open]));
+                }
+                pieces.push(Piece::Icon(region));
+            }
+            None => pieces.push(Piece::Letters(&rest[..=close])),
+        }
+        rest = &rest[close + 1..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Letters(rest));
+    }
+    pieces
+}
+
+/// How wide a piece is when drawn at `size`. Pictures are as tall as the text.
+fn piece_width(assets: &Assets, piece: &Piece, size: f32) -> f32 {
+    match piece {
+        Piece::Letters(letters) => {
+            measure_text(letters, assets.font.as_ref(), size.round() as u16, 1.0).width
+        }
+        Piece::Icon(region) => size * region.size().x / region.size().y,
+    }
+}
+
+/// How wide `text` is when drawn at `size`, inline pictures included.
+/// `text`: The text, with any inline pictures written as "{name}".
+/// `size`: The height of the text, in virtual screen pixels.
+pub fn text_width(assets: &Assets, text: &str, size: f32) -> f32 {
+    pieces(assets, text)
+        .iter()
+        .map(|piece| piece_width(assets, piece, size))
+        .sum()
+}
+
+/// Draws one line of text.
+/// `text`: The text, with any inline pictures written as "{name}".
+/// `x`: Where the text lines up horizontally, as `style.align` says.
+/// `y`: The baseline the letters sit on.
+/// `style`: The size, color, alignment, outline and shadow.
+pub fn draw_text_line(assets: &Assets, text: &str, x: f32, y: f32, style: TextStyle) {
+    let pieces = pieces(assets, text);
+    let widths: Vec<f32> = pieces
+        .iter()
+        .map(|piece| piece_width(assets, piece, style.size))
+        .collect();
+    let total: f32 = widths.iter().sum();
+    let left = match style.align {
+        Align::Left => x,
+        Align::Center => x - total / 2.0,
+        Align::Right => x - total,
+    };
+
+    // Draw the shadow and the outline first, as copies of the letters moved a little,
+    // then the letters themselves on top.
+    let mut layers: Vec<(Vec2, Color)> = Vec::new();
+    if let Some(color) = style.shadow {
+        layers.push((Vec2::splat(TEXT_SHADOW_OFFSET), color));
+    }
+    if let Some(color) = style.outline {
+        for offset_x in [-1.0, 0.0, 1.0] {
+            for offset_y in [-1.0, 0.0, 1.0] {
+                if offset_x != 0.0 || offset_y != 0.0 {
+                    let offset = Vec2::new(offset_x, offset_y) * TEXT_OUTLINE_WIDTH;
+                    layers.push((offset, color));
+                }
+            }
+        }
+    }
+    layers.push((Vec2::ZERO, style.color));
+
+    for (offset, color) in layers {
+        let is_letters = offset != Vec2::ZERO;
+        let mut piece_x = left + offset.x;
+        for (piece, width) in pieces.iter().zip(&widths) {
+            match piece {
+                Piece::Letters(letters) => {
+                    draw_text_ex(
+                        letters,
+                        piece_x,
+                        y + offset.y,
+                        TextParams {
+                            font: assets.font.as_ref(),
+                            font_size: style.size.round() as u16,
+                            color,
+                            ..TextParams::default()
+                        },
+                    );
+                }
+                // Pictures keep their own colors, so they are only drawn once, on top.
+                Piece::Icon(region) if !is_letters => region.draw(
+                    piece_x,
+                    // Sit the picture on the baseline, like a capital letter.
+                    y - style.size * 0.8,
+                    WHITE,
+                    DrawTextureParams {
+                        dest_size: Some(Vec2::new(*width, style.size)),
+                        ..DrawTextureParams::default()
+                    },
+                ),
+                Piece::Icon(_) => {}
+            }
+            piece_x += width;
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! The components entities are made of. Each one describes a single aspect of an entity,
+//! and only means something to the systems that look for it.
+
+use crate::assets::Assets;
+use crate::atlas::TextureRegion;
+use crate::constants::*;
+use crate::entities::chicken::Chicken;
+use crate::entities::platform::Platform;
+use macroquad::prelude::*;
+
+/// Where an entity is, how big it is, and how far it is turned.
+pub struct Transform {
+    /// The entity's position (x, y) and dimensions (w, h).
+    pub rect: Rect,
+    /// Rotation in radians, around the middle of the rectangle.
+    pub rotation: f32,
+}
+
+impl Transform {
+    /// An unrotated transform covering `rect`.
+    pub fn new(rect: Rect) -> Transform {
+        Transform {
+            rect,
+            rotation: 0.0,
+        }
+    }
+}
+
+/// Which image a sprite shows. Looked up in the `Assets` when drawing.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpriteImage {
+    Background,
+    Cloud,
+    Platform,
+    ChocoBar,
+    PlatformTile,
+    CookieTile,
+    ChocoTile,
+    House,
+    Egg,
+    Spike,
+    Chicken,
+}
+
+impl SpriteImage {
+    /// The loaded image, in its texture atlas.
+    pub fn texture(self, assets: &Assets) -> &TextureRegion {
+        match self {
+            SpriteImage::Background => &assets.background,
+            SpriteImage::Cloud => &assets.cloud,
+            SpriteImage::Platform => &assets.platform,
+            SpriteImage::ChocoBar => &assets.choco_bar,
+            SpriteImage::PlatformTile => &assets.platform_tile,
+            SpriteImage::CookieTile => &assets.cookie_tile,
+            SpriteImage::ChocoTile => &assets.choco_tile,
+            SpriteImage::House => &assets.house,
+            SpriteImage::Egg => &assets.egg,
+            SpriteImage::Spike => &assets.spike,
+            SpriteImage::Chicken => &assets.chicken,
+        }
+    }
+}
+
+/// The layers sprites are drawn in, from the back to the front.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Layer {
+    Sky,
+    Clouds,
+    Platforms,
+    Buildings,
+    Items,
+    Hazards,
+    Enemies,
+}
+
+/// How an entity is drawn.
+pub struct Sprite {
+    /// The image to draw.
+    pub image: SpriteImage,
+    /// Which layer the sprite is drawn in, so e.g. chickens are always in front of platforms.
+    pub layer: Layer,
+    /// Tint color (WHITE means no tint), also used to fade the sprite in and out.
+    pub tint: Color,
+    /// If set, the image is repeated side by side at this size across the entity's width,
+    /// instead of being stretched over the whole entity.
+    pub tile_size: Option<Vec2>,
+    /// How far from the entity's position the sprite is drawn (e.g., to shake it).
+    pub offset: Vec2,
+    /// Draws the image upside down.
+    pub flip_y: bool,
+}
+
+impl Sprite {
+    /// A sprite that stretches `image` over the whole entity, untinted.
+    pub fn new(image: SpriteImage, layer: Layer) -> Sprite {
+        Sprite {
+            image,
+            layer,
+            tint: WHITE,
+            tile_size: None,
+            offset: Vec2::ZERO,
+            flip_y: false,
+        }
+    }
+
+    /// A sprite that repeats `image` at `tile_size` across the entity, untinted.
+    pub fn tiled(image: SpriteImage, layer: Layer, tile_size: Vec2) -> Sprite {
+        Sprite {
+            tile_size: Some(tile_size),
+            ..Sprite::new(image, layer)
+        }
+    }
+}
+
+/// Makes an entity something the players can touch.
+pub struct Collider {
+    /// How far inside the entity's edges the touchable area starts,
+    /// so touching only counts once the sprites properly overlap.
+    pub margin: f32,
+}
+
+impl Collider {
+    /// The usual collider, `COLLISION_MARGIN` inside the entity's edges.
+    pub const DEFAULT: Collider = Collider {
+        margin: COLLISION_MARGIN,
+    };
+
+    /// The touchable area of an entity covering `rect`.
+    pub fn bounds(&self, rect: Rect) -> Rect {
+        Rect {
+            x: rect.x + self.margin,       // Move left edge inwards
+            y: rect.y + self.margin,       // Move top edge downwards
+            w: rect.w - self.margin * 2.0, // Reduce width
+            h: rect.h - self.margin * 2.0, // Reduce height
+        }
+    }
+}
+
+/// Something the players collect by touching it. It disappears once collected.
+pub struct Pickup {
+    /// How many eggs the player carries away.
+    pub eggs: u32,
+    /// How many points the player scores.
+    pub points: u32,
+}
+
+/// Something that costs the players a life when they touch it.
+pub enum Hazard {
+    /// Always hurts (e.g., spikes).
+    Sharp,
+    /// Hurts on contact, unless the player lands on it from above, which hurts it instead
+    /// (e.g., chickens). It is defeated once its health runs out.
+    Stompable {
+        /// Remaining hit points.
+        health: u32,
+        /// Hit points it started with. Tougher hazards are worth more points.
+        max_health: u32,
+        /// Seconds left before it can be hurt (or hurt anyone) again.
+        hit_cooldown: f32,
+    },
+}
+
+impl Hazard {
+    /// Returns `true` once a stompable hazard has no health left. Defeated hazards are harmless.
+    pub fn is_defeated(&self) -> bool {
+        matches!(self, Hazard::Stompable { health: 0, .. })
+    }
+
+    /// Returns `true` while a stompable hazard is recovering from being stomped.
+    pub fn is_recovering(&self) -> bool {
+        matches!(self, Hazard::Stompable { hit_cooldown, .. } if *hit_cooldown > 0.0)
+    }
+
+    /// Deals one point of damage, if the hazard is stompable and isn't still recovering.
+    /// Returns `true` if the hit defeated it.
+    pub fn take_hit(&mut self) -> bool {
+        if self.is_recovering() || self.is_defeated() {
+            return false;
+        }
+        match self {
+            Hazard::Sharp => false,
+            Hazard::Stompable {
+                health,
+                hit_cooldown,
+                ..
+            } => {
+                *health -= 1;
+                *hit_cooldown = CHICKEN_HIT_COOLDOWN;
+                *health == 0
+            }
+        }
+    }
+}
+
+/// What drives an entity that acts on its own.
+pub enum Behaviour {
+    /// A platform the players can stand on.
+    Platform(Platform),
+    /// A chicken flying around the level.
+    Chicken(Chicken),
+    /// Part of the scenery, moved `spacing` pixels ahead once the players have left it
+    /// far behind, so the scenery never runs out.
+    Repeat {
+        /// How far ahead to move it: the total width of all the copies side by side.
+        spacing: f32,
+    },
+}
@@ -0,0 +1,217 @@
+//! A uniform grid over the world, used to find the entities in an area without looking at
+//! every entity. This is the "broadphase": it quickly narrows thousands of entities down to the
+//! handful near the player (for collisions) or on screen (for drawing), and only those are then
+//! checked exactly.
+
+use crate::world::{Components, Entity};
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+/// The columns and rows of grid cells an entity covers, from its top-left to its bottom-right cell.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct CellRange {
+    first_column: i32,
+    first_row: i32,
+    last_column: i32,
+    last_row: i32,
+}
+
+impl CellRange {
+    /// Every cell in the range, as (column, row).
+    fn cells(self) -> impl Iterator<Item = (i32, i32)> {
+        (self.first_column..=self.last_column)
+            .flat_map(move |column| (self.first_row..=self.last_row).map(move |row| (column, row)))
+    }
+}
+
+/// Splits the world into square cells and remembers which entities overlap each cell.
+/// Entities are listed in every cell their rectangle touches, so large entities
+/// (like background images) are found from any part of them.
+/// Only cells that hold something are stored, so the grid can cover an endless level.
+pub struct SpatialGrid {
+    /// The width and height of each cell, in pixels.
+    cell_size: f32,
+    /// The entities in each non-empty cell, keyed by (column, row).
+    cells: HashMap<(i32, i32), Vec<Entity>>,
+    /// The cells each entity was last placed in, so moving or removing it only touches those cells.
+    placed: Components<CellRange>,
+}
+
+impl SpatialGrid {
+    /// Creates an empty grid.
+    /// `cell_size`: The width and height of each cell. Roughly the size of a platform works well:
+    /// much smaller and big entities fill many cells, much bigger and each cell holds too many.
+    pub fn new(cell_size: f32) -> SpatialGrid {
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+            placed: Components::new(),
+        }
+    }
+
+    /// The range of cells `rect` touches.
+    fn cell_range(&self, rect: Rect) -> CellRange {
+        CellRange {
+            first_column: (rect.x / self.cell_size).floor() as i32,
+            first_row: (rect.y / self.cell_size).floor() as i32,
+            last_column: (rect.right() / self.cell_size).floor() as i32,
+            last_row: (rect.bottom() / self.cell_size).floor() as i32,
+        }
+    }
+
+    /// Places `entity` in the cells covered by `rect`, moving it out of the cells it was in before.
+    /// Needs to be called whenever an entity is created or moved, or queries will miss it.
+    pub fn update(&mut self, entity: Entity, rect: Rect) {
+        let range = self.cell_range(rect);
+        // Most moves stay inside the same cells, and then there is nothing to do.
+        if self.placed.get(entity) == Some(&range) {
+            return;
+        }
+        self.remove(entity);
+        for cell in range.cells() {
+            self.cells.entry(cell).or_default().push(entity);
+        }
+        self.placed.insert(entity, range);
+    }
+
+    /// Takes `entity` out of the grid. Does nothing if it isn't in it.
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(range) = self.placed.remove(entity) else {
+            return;
+        };
+        for cell in range.cells() {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|other| *other != entity);
+                // Forget empty cells, so memory doesn't grow as the players travel.
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Every entity in the cells that `area` touches, each listed once, in slot order (like the
+    /// component lists). This can include entities that are near `area` but don't quite
+    /// overlap it, so callers still check the exact rectangles.
+    pub fn query(&self, area: Rect) -> Vec<Entity> {
+        let mut found: Vec<Entity> = self
+            .cell_range(area)
+            .cells()
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        // Entities spanning several cells were found once per cell.
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::GRID_CELL_SIZE;
+    use crate::world::components::Transform;
+    use crate::world::{Entity, World};
+    use macroquad::prelude::*;
+    use macroquad::rand::{gen_range, srand};
+
+    /// A random rectangle, from tiny to several cells wide. Some sit exactly on cell edges,
+    /// or just across one, since those are the easiest to get wrong.
+    fn random_rect() -> Rect {
+        let cell = GRID_CELL_SIZE;
+        let size = Vec2::new(gen_range(1.0, cell * 3.0), gen_range(1.0, cell * 3.0));
+        let corner = Vec2::new(
+            gen_range(-cell * 8.0, cell * 8.0),
+            gen_range(-cell * 4.0, cell * 4.0),
+        );
+        match gen_range(0, 4) {
+            // Starting exactly on a cell edge.
+            0 => Rect::new((corner.x / cell).round() * cell, corner.y, cell, size.y),
+            // Ending exactly on a cell edge.
+            1 => Rect::new(
+                corner.x,
+                (corner.y / cell).round() * cell - size.y,
+                size.x,
+                size.y,
+            ),
+            // Straddling a cell edge by a hair.
+            2 => Rect::new((corner.x / cell).round() * cell - 0.5, corner.y, 1.0, 1.0),
+            _ => Rect::new(corner.x, corner.y, size.x, size.y),
+        }
+    }
+
+    /// Every entity whose rectangle overlaps `area`, found by looking at every entity.
+    fn scan(world: &World, area: Rect) -> Vec<Entity> {
+        world
+            .transforms
+            .iter()
+            .filter(|(_, transform)| transform.rect.overlaps(&area))
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    /// Checks that the grid finds every entity a scan does, and nothing that no longer exists.
+    fn check_queries(world: &World) {
+        for _ in 0..500 {
+            let area = random_rect();
+            let found = world.query(area);
+            for entity in scan(world, area) {
+                assert!(
+                    found.contains(&entity),
+                    "{entity:?} is missing from {area:?}"
+                );
+            }
+            for entity in found {
+                assert!(world.is_alive(entity), "{entity:?} was despawned");
+            }
+        }
+    }
+
+    #[test]
+    fn queries_match_a_scan() {
+        srand(7);
+        let mut world = World::new();
+        for _ in 0..400 {
+            let entity = world.spawn();
+            world.place(entity, Transform::new(random_rect()));
+        }
+        check_queries(&world);
+    }
+
+    #[test]
+    fn queries_match_a_scan_after_moving_and_despawning() {
+        srand(11);
+        let mut world = World::new();
+        let entities: Vec<Entity> = (0..400)
+            .map(|_| {
+                let entity = world.spawn();
+                world.place(entity, Transform::new(random_rect()));
+                entity
+            })
+            .collect();
+
+        for (i, entity) in entities.iter().enumerate() {
+            match i % 4 {
+                // Move some entities a little, the way systems do...
+                0 => {
+                    let transform = world.transforms.get_mut(*entity).unwrap();
+                    transform.rect.x += gen_range(-40.0, 40.0);
+                    transform.rect.y += gen_range(-40.0, 40.0);
+                    let rect = transform.rect;
+                    world.grid.update(*entity, rect);
+                }
+                // ...move others far away...
+                1 => world.place(*entity, Transform::new(random_rect())),
+                // ...and despawn some, reusing their slots for new entities.
+                2 => {
+                    world.despawn(*entity);
+                    let new = world.spawn();
+                    world.place(new, Transform::new(random_rect()));
+                }
+                _ => {}
+            }
+        }
+        check_queries(&world);
+    }
+}
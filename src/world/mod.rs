@@ -0,0 +1,231 @@
+//! The entity-component store that holds everything in a level (and in the sky behind it)
+//! except the players.
+//!
+//! An entity is nothing but an id. What it *is* depends on which components it has: an egg is a
+//! transform, a sprite, a collider and a pickup; a spike swaps the pickup for a hazard; a chicken
+//! adds a velocity and a behaviour. Systems each loop over the components they care about, so a
+//! new kind of object is made by combining existing components instead of adding another list
+//! and the loops to update, collide and draw it.
+
+pub mod components;
+pub mod grid;
+pub mod systems;
+
+use crate::constants::GRID_CELL_SIZE;
+use components::{Behaviour, Collider, Hazard, Pickup, Sprite, Transform};
+use grid::SpatialGrid;
+use macroquad::prelude::*;
+
+/// A handle to one entity in a `World`.
+/// Once the entity is despawned, the handle stops finding any components, even if its slot
+/// is reused for a new entity, so it is safe to hold on to (e.g., the platform a player stands on).
+/// Handles sort by slot, the order the component lists are kept in.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Entity {
+    /// The entity's slot in every component list.
+    index: u32,
+    /// Counts how many times the slot has been reused, to tell old handles apart from new ones.
+    generation: u32,
+}
+
+/// One kind of component, stored by entity slot.
+/// Each slot remembers which generation of entity the component belongs to.
+pub struct Components<T> {
+    slots: Vec<Option<(u32, T)>>,
+}
+
+impl<T> Components<T> {
+    /// Creates an empty list of components.
+    pub fn new() -> Components<T> {
+        Components { slots: Vec::new() }
+    }
+
+    /// Gives `entity` this component, replacing any it had before.
+    pub fn insert(&mut self, entity: Entity, component: T) {
+        let index = entity.index as usize;
+        if self.slots.len() <= index {
+            // `resize_with` grows the list, filling the new slots with `None`.
+            self.slots.resize_with(index + 1, || None);
+        }
+        self.slots[index] = Some((entity.generation, component));
+    }
+
+    /// Takes this component away from `entity`, returning it if it had one.
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let slot = self.slots.get_mut(entity.index as usize)?;
+        match slot {
+            Some((generation, _)) if *generation == entity.generation => {
+                slot.take().map(|(_, component)| component)
+            }
+            _ => None,
+        }
+    }
+
+    /// The component belonging to `entity`, if it has one.
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        match self.slots.get(entity.index as usize)? {
+            Some((generation, component)) if *generation == entity.generation => Some(component),
+            _ => None,
+        }
+    }
+
+    /// The component belonging to `entity`, if it has one, to change it.
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.slots.get_mut(entity.index as usize)? {
+            Some((generation, component)) if *generation == entity.generation => Some(component),
+            _ => None,
+        }
+    }
+
+    /// Every entity with this component, along with the component, in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.as_ref().map(|(generation, component)| {
+                let entity = Entity {
+                    index: index as u32,
+                    generation: *generation,
+                };
+                (entity, component)
+            })
+        })
+    }
+
+    /// Every entity with this component, along with the component to change it, in slot order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                slot.as_mut().map(|(generation, component)| {
+                    let entity = Entity {
+                        index: index as u32,
+                        generation: *generation,
+                    };
+                    (entity, component)
+                })
+            })
+    }
+}
+
+impl<T> Default for Components<T> {
+    fn default() -> Self {
+        Components::new()
+    }
+}
+
+/// All the entities in a level (or in the sky), and their components.
+/// The component lists are public so systems can borrow several of them at once,
+/// e.g. reading velocities while moving transforms.
+pub struct World {
+    /// The current generation of every slot.
+    generations: Vec<u32>,
+    /// Slots whose entity was despawned, ready to be reused.
+    free: Vec<u32>,
+    /// Where each entity is and how big it is.
+    pub transforms: Components<Transform>,
+    /// How fast each moving entity is going (pixels per second).
+    pub velocities: Components<Vec2>,
+    /// How each visible entity is drawn.
+    pub sprites: Components<Sprite>,
+    /// The area of each entity the players can touch.
+    pub colliders: Components<Collider>,
+    /// What drives entities that act on their own (platforms, chickens, ...).
+    pub behaviours: Components<Behaviour>,
+    /// What the players get for touching each collectable entity.
+    pub pickups: Components<Pickup>,
+    /// How each dangerous entity hurts the players.
+    pub hazards: Components<Hazard>,
+    /// Which entities are where, to find the ones near the players or on screen quickly.
+    /// Systems that move entities keep it up to date.
+    pub grid: SpatialGrid,
+}
+
+impl World {
+    /// Creates a world with no entities in it.
+    pub fn new() -> World {
+        World {
+            generations: Vec::new(),
+            free: Vec::new(),
+            transforms: Components::new(),
+            velocities: Components::new(),
+            sprites: Components::new(),
+            colliders: Components::new(),
+            behaviours: Components::new(),
+            pickups: Components::new(),
+            hazards: Components::new(),
+            grid: SpatialGrid::new(GRID_CELL_SIZE),
+        }
+    }
+
+    /// Creates a new entity with no components, reusing a free slot if there is one.
+    pub fn spawn(&mut self) -> Entity {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.generations.push(0);
+                self.generations.len() as u32 - 1
+            }
+        };
+        Entity {
+            index,
+            generation: self.generations[index as usize],
+        }
+    }
+
+    /// Gives `entity` its position and size, and places it in the grid.
+    /// Entities get their transform this way (instead of inserting it directly)
+    /// so the grid always knows about them.
+    pub fn place(&mut self, entity: Entity, transform: Transform) {
+        self.grid.update(entity, transform.rect);
+        self.transforms.insert(entity, transform);
+    }
+
+    /// Removes `entity` and all of its components. Does nothing if it is already gone.
+    pub fn despawn(&mut self, entity: Entity) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        self.transforms.remove(entity);
+        self.velocities.remove(entity);
+        self.sprites.remove(entity);
+        self.colliders.remove(entity);
+        self.behaviours.remove(entity);
+        self.pickups.remove(entity);
+        self.hazards.remove(entity);
+        self.grid.remove(entity);
+        // Bump the generation so old handles to this slot stop working.
+        self.generations[entity.index as usize] += 1;
+        self.free.push(entity.index);
+    }
+
+    /// Returns `true` if `entity` has been spawned and not despawned since.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        // Despawning bumps the slot's generation, so only live entities match it.
+        self.generations.get(entity.index as usize) == Some(&entity.generation)
+    }
+
+    /// The number of entities in the world.
+    pub fn entity_count(&self) -> usize {
+        self.generations.len() - self.free.len()
+    }
+
+    /// Every entity that might overlap `area`, in slot order. Nearby entities that don't quite
+    /// overlap can be included too, so check their exact bounds afterwards.
+    pub fn query(&self, area: Rect) -> Vec<Entity> {
+        self.grid.query(area)
+    }
+
+    /// The area covered by `entity` that players can touch: its transform shrunk by its collider,
+    /// or `None` if it has no transform or no collider.
+    pub fn collision_bounds(&self, entity: Entity) -> Option<Rect> {
+        let transform = self.transforms.get(entity)?;
+        let collider = self.colliders.get(entity)?;
+        Some(collider.bounds(transform.rect))
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        World::new()
+    }
+}
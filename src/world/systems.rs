@@ -0,0 +1,129 @@
+//! The systems every kind of entity shares. Each one loops over the entities that have
+//! the components it needs and ignores the rest. The systems for behaviours that only one kind
+//! of entity has live next to that kind, in `entities`.
+
+use crate::assets::Assets;
+use crate::constants::{CULL_MARGIN, HAZARD_OUTLINE_COLORS, HAZARD_OUTLINE_WIDTHS};
+use crate::world::components::{Behaviour, Hazard, Sprite};
+use crate::world::{Entity, World};
+use macroquad::prelude::*;
+
+/// Moves every entity with a velocity by that velocity.
+/// `delta_time`: The time in seconds since the last frame update.
+pub fn movement_system(world: &mut World, delta_time: f32) {
+    for (entity, velocity) in world.velocities.iter() {
+        if let Some(transform) = world.transforms.get_mut(entity) {
+            // Update position: position = position + velocity * time
+            transform.rect.x += velocity.x * delta_time;
+            transform.rect.y += velocity.y * delta_time;
+            world.grid.update(entity, transform.rect);
+        }
+    }
+}
+
+/// Counts down the time stompable hazards spend recovering after being hit.
+/// `delta_time`: The time in seconds since the last frame update.
+pub fn hazard_system(world: &mut World, delta_time: f32) {
+    for (_, hazard) in world.hazards.iter_mut() {
+        if let Hazard::Stompable { hit_cooldown, .. } = hazard {
+            *hit_cooldown = (*hit_cooldown - delta_time).max(0.0);
+        }
+    }
+}
+
+/// Moves repeating scenery that has been left far behind ahead again.
+/// `far_behind_x`: Anything completely left of this x position is moved ahead.
+pub fn repeat_system(world: &mut World, far_behind_x: f32) {
+    for (entity, behaviour) in world.behaviours.iter() {
+        let Behaviour::Repeat { spacing } = behaviour else {
+            continue;
+        };
+        if let Some(transform) = world.transforms.get_mut(entity) {
+            if transform.rect.right() < far_behind_x {
+                transform.rect.x += spacing;
+                world.grid.update(entity, transform.rect);
+            }
+        }
+    }
+}
+
+/// Draws every entity with a sprite that is on screen, layer by layer from the back to the front.
+/// Within a layer, entities are drawn in slot order.
+/// `view`: The area of the world on screen. Entities far outside it are skipped.
+pub fn draw_system(world: &World, assets: &Assets, view: Rect) {
+    // --- Culling ---
+    // Only look at the entities the grid has near the view, instead of every entity in the world.
+    let area = Rect::new(
+        view.x - CULL_MARGIN,
+        view.y - CULL_MARGIN,
+        view.w + 2.0 * CULL_MARGIN,
+        view.h + 2.0 * CULL_MARGIN,
+    );
+    let mut sprites: Vec<(Entity, &Sprite)> = world
+        .query(area)
+        .into_iter()
+        .filter_map(|entity| Some((entity, world.sprites.get(entity)?)))
+        .collect();
+    // `sort_by_key` is stable, so entities in the same layer keep their order.
+    sprites.sort_by_key(|(_, sprite)| sprite.layer);
+
+    for (entity, sprite) in sprites {
+        let Some(transform) = world.transforms.get(entity) else {
+            continue;
+        };
+        let texture = sprite.image.texture(assets);
+        let rect = transform.rect;
+        let position = rect.point() + sprite.offset;
+        match sprite.tile_size {
+            // Repeat the tile side by side across the entity's width.
+            Some(tile_size) => {
+                let mut x = position.x;
+                while x < position.x + rect.w {
+                    texture.draw(
+                        x,
+                        position.y,
+                        sprite.tint,
+                        DrawTextureParams {
+                            dest_size: Some(tile_size),
+                            ..DrawTextureParams::default()
+                        },
+                    );
+                    x += tile_size.x;
+                }
+            }
+            // Stretch the image over the whole entity.
+            None => texture.draw(
+                position.x,
+                position.y,
+                sprite.tint,
+                DrawTextureParams {
+                    dest_size: Some(rect.size()),
+                    rotation: transform.rotation,
+                    flip_y: sprite.flip_y,
+                    ..DrawTextureParams::default()
+                },
+            ),
+        }
+    }
+}
+
+/// Outlines every dangerous entity on screen (spikes and chickens that can still hurt), for the
+/// high-contrast setting. The outline follows the part that hurts, not the whole picture.
+/// `view`: The area of the world on screen. Entities outside it are skipped.
+pub fn hazard_outline_system(world: &World, view: Rect) {
+    for entity in world.query(view) {
+        let Some(hazard) = world.hazards.get(entity) else {
+            continue;
+        };
+        if hazard.is_defeated() {
+            continue;
+        }
+        let Some(bounds) = world.collision_bounds(entity) else {
+            continue;
+        };
+        // A thick dark line with a thin bright one on top.
+        for (color, width) in HAZARD_OUTLINE_COLORS.into_iter().zip(HAZARD_OUTLINE_WIDTHS) {
+            draw_rectangle_lines(bounds.x, bounds.y, bounds.w, bounds.h, width, color);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Checks that every generated level can be completed, over thousands of seeds,
+//! and that players lost in endless levels come back on ground that still exists.
+//! Generating levels needs no window, so this runs anywhere with `cargo test`.
+
+use easter_egg::constants::*;
+use easter_egg::level::Level;
+use easter_egg::options::DifficultySettings;
+
+/// How many seeds are tried for each difficulty.
+const SEEDS: u64 = 3000;
+
+/// The bottom of the level, as the game places it for its usual window height.
+const BOTTOM: f32 = VIRTUAL_HEIGHT;
+
+/// Generates a level from every seed and checks that its house and enough eggs can be reached.
+fn check_seeds(settings: &DifficultySettings) {
+    for seed in 0..SEEDS {
+        let level = Level::generate(seed, BOTTOM, settings);
+        assert!(
+            level.is_completable(settings.eggs_needed_for_win),
+            "the level from seed {seed} can't be completed"
+        );
+    }
+}
+
+#[test]
+fn easy_levels_can_be_completed() {
+    check_seeds(&DifficultySettings::EASY);
+}
+
+#[test]
+fn normal_levels_can_be_completed() {
+    check_seeds(&DifficultySettings::NORMAL);
+}
+
+#[test]
+fn hard_levels_can_be_completed() {
+    check_seeds(&DifficultySettings::HARD);
+}
+
+/// The hardest Custom difficulty: as many spikes and as few eggs as can be chosen, and the
+/// most eggs needed to win, so most levels need repairing.
+#[test]
+fn hardest_custom_levels_can_be_completed() {
+    let settings = DifficultySettings {
+        spike_chance: 100,
+        egg_chance: 10,
+        eggs_needed_for_win: 15,
+        ..DifficultySettings::HARD
+    };
+    check_seeds(&settings);
+}
+
+/// Endless levels throw away the terrain behind the players, so a player lost far from the
+/// start must come back on ground that is still there, not at the start of the level.
+#[test]
+fn endless_respawns_land_on_ground_that_is_still_loaded() {
+    for seed in 0..50 {
+        let mut level = Level::endless(seed, BOTTOM);
+        while level.generated_to < 40.0 * VIRTUAL_WIDTH {
+            level.extend_endless(&DifficultySettings::NORMAL, ENDLESS_MAX_RAMP);
+        }
+        let rear_x = level.generated_to - 3.0 * VIRTUAL_WIDTH;
+        level.discard_before(rear_x - 2.0 * VIRTUAL_WIDTH);
+        let map = &level.tile_map;
+        let first_x = map.origin.x + map.first_column as f32 * TILE_SIZE;
+
+        for step in 0..100 {
+            let lost_at = rear_x + step as f32 * TILE_SIZE / 3.0;
+            let respawn = map
+                .ground_near(lost_at)
+                .expect("no ground left in the level");
+            assert!(respawn.x >= first_x && respawn.x <= lost_at + VIRTUAL_WIDTH);
+            let surface = map.surface_y(respawn.x).unwrap();
+            assert_eq!(respawn.y, surface - PLAYER_SIZE.y / 2.0);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Runs online races on loopback: a headless race server in a thread, and several clients
+//! talking to it over real UDP sockets, the way games on different computers would.
+
+use easter_egg::constants::*;
+use easter_egg::entities::items::spawn_house;
+use easter_egg::entities::player::PlayerInput;
+use easter_egg::level::Level;
+use easter_egg::net::{RaceConnection, RaceServer, RacerState};
+use std::time::{Duration, Instant};
+
+/// How long a test waits for something to happen before failing.
+/// Running from the start to the house takes about two seconds.
+const PATIENCE: Duration = Duration::from_secs(10);
+
+/// The time of one frame at 60 frames per second.
+const FRAME_TIME: f32 = 1.0 / 60.0;
+
+/// Where the house of the test track stands, a short run from the start.
+const TRACK_HOUSE_X: f32 = 800.0;
+
+/// A short, flat track with the house close to the start, so racers holding right finish
+/// quickly without having to jump anything.
+fn short_track(_seed: u64) -> Level {
+    // Everything past the start of the terrain is kept flat.
+    let mut level = Level::empty(RACE_LEVEL_BOTTOM, TERRAIN_START_X);
+    level.tile_map.extend(TERRAIN_END_X, level.flat_from, 0.0);
+    let ground = level.tile_map.surface_y(TRACK_HOUSE_X).unwrap();
+    level.house = Some(spawn_house(&mut level.world, TRACK_HOUSE_X, ground));
+    level
+}
+
+/// Starts a race server on a free loopback port, in its own thread, and returns its address.
+/// The thread runs until the test ends.
+fn start_server(build_level: fn(u64) -> Level) -> String {
+    let mut server = RaceServer::bind_with_levels("127.0.0.1:0", build_level).unwrap();
+    let address = server.local_addr().unwrap().to_string();
+    std::thread::spawn(move || server.run());
+    address
+}
+
+/// A racer in a test: their connection, and the racers from the newest state they received.
+struct TestRacer {
+    connection: RaceConnection,
+    racers: Vec<RacerState>,
+}
+
+impl TestRacer {
+    fn join(address: &str) -> TestRacer {
+        TestRacer {
+            connection: RaceConnection::connect(address).unwrap(),
+            racers: Vec::new(),
+        }
+    }
+
+    /// This racer, as the server last described them.
+    fn me(&self) -> Option<&RacerState> {
+        self.find(self.connection.player_id)
+    }
+
+    /// The racer with id `player_id`, as the server last described them.
+    fn find(&self, player_id: u8) -> Option<&RacerState> {
+        self.racers
+            .iter()
+            .find(|racer| racer.player_id == player_id)
+    }
+}
+
+/// Sends frames of input from every racer (holding right for those in `running`, nothing for
+/// the rest) and reads what the server sends back, until `done` is true.
+/// Inputs are sent about as often as a game running at 60 frames per second sends them,
+/// since the server won't move racers faster than real time.
+fn race_until(
+    racers: &mut [&mut TestRacer],
+    running: &[bool],
+    done: impl Fn(&[&mut TestRacer]) -> bool,
+) {
+    let started = Instant::now();
+    while !done(racers) {
+        assert!(
+            started.elapsed() < PATIENCE,
+            "timed out waiting for the race"
+        );
+        for (racer, running) in racers.iter_mut().zip(running) {
+            let input = PlayerInput {
+                right: *running,
+                ..PlayerInput::default()
+            };
+            racer.connection.send_input(input, FRAME_TIME).unwrap();
+            if let Some((_, states)) = racer.connection.receive().unwrap() {
+                racer.racers = states;
+            }
+        }
+        std::thread::sleep(Duration::from_secs_f32(FRAME_TIME));
+    }
+}
+
+#[test]
+fn racers_get_their_own_ids_and_see_each_other() {
+    let address = start_server(short_track);
+    let mut first = TestRacer::join(&address);
+    let mut second = TestRacer::join(&address);
+    let mut third = TestRacer::join(&address);
+    let ids = [
+        first.connection.player_id,
+        second.connection.player_id,
+        third.connection.player_id,
+    ];
+    assert!(ids[0] != ids[1] && ids[1] != ids[2] && ids[0] != ids[2]);
+
+    race_until(
+        &mut [&mut first, &mut second, &mut third],
+        &[false; 3],
+        |racers| {
+            racers
+                .iter()
+                .all(|racer| ids.iter().all(|id| racer.find(*id).is_some()))
+        },
+    );
+}
+
+#[test]
+fn ids_are_reused_once_a_racer_leaves() {
+    let address = start_server(short_track);
+    let first = TestRacer::join(&address);
+    let mut second = TestRacer::join(&address);
+    let left_id = first.connection.player_id;
+    first.connection.leave();
+    race_until(&mut [&mut second], &[false], |racers| {
+        racers[0].me().is_some() && racers[0].find(left_id).is_none()
+    });
+
+    let third = TestRacer::join(&address);
+    assert_eq!(third.connection.player_id, left_id);
+    assert_ne!(third.connection.player_id, second.connection.player_id);
+}
+
+#[test]
+fn finishing_racers_are_given_their_places() {
+    let address = start_server(short_track);
+    let mut first = TestRacer::join(&address);
+    let mut second = TestRacer::join(&address);
+    let first_id = first.connection.player_id;
+    let second_id = second.connection.player_id;
+
+    // The first racer runs for the house while the second waits at the start.
+    race_until(&mut [&mut first, &mut second], &[true, false], |racers| {
+        racers[0].me().is_some_and(|me| me.place != 0)
+    });
+    assert_eq!(first.me().unwrap().place, 1);
+
+    // Then the second racer runs too, and comes in second.
+    race_until(&mut [&mut first, &mut second], &[false, true], |racers| {
+        racers
+            .iter()
+            .all(|racer| racer.find(second_id).is_some_and(|other| other.place != 0))
+    });
+    for racer in [&first, &second] {
+        assert_eq!(racer.find(first_id).unwrap().place, 1);
+        assert_eq!(racer.find(second_id).unwrap().place, 2);
+    }
+}